@@ -1,5 +1,5 @@
 /* eslint-disable @typescript-eslint/no-non-null-assertion */
-import init, { WasmGame, WasmEngine, WasmMove, name } from '../../pkg/bitboard_x';
+import init, { WasmGame, WasmEngine, WasmMove, name, default_hash_mb } from '../../pkg/bitboard_x';
 import { BoardView, NullBoardView } from './board-view';
 import { InitBoardView2D } from './board-view-2d';
 
@@ -16,7 +16,7 @@ export async function initialize(config: Config) {
   boardView?.setOnClickCallback((square) => picker.onSquareClicked(square));
 
   console.log(`✅ Initializing engine ${name()}`);
-  engine = new WasmEngine();
+  engine = new WasmEngine(default_hash_mb());
   return controller;
 }
 
@@ -2,7 +2,7 @@
 use wasm_bindgen::prelude::*;
 
 use crate::core::{game_state::GameState, move_gen::*, position::*, types::*};
-use crate::engine::Engine;
+use crate::engine::{DEFAULT_HASH_MB, Engine};
 use crate::utils;
 
 #[wasm_bindgen]
@@ -10,6 +10,11 @@ pub fn name() -> String {
     Engine::name()
 }
 
+#[wasm_bindgen]
+pub fn default_hash_mb() -> usize {
+    DEFAULT_HASH_MB
+}
+
 // ------------------------------ Move Binding ---------------------------------
 
 #[wasm_bindgen]
@@ -182,9 +187,10 @@ pub struct WasmEngine {
 
 #[wasm_bindgen]
 impl WasmEngine {
+    /// `hash_mb` is the size of the transposition table in megabytes
     #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
-        Self { engine: Engine::new() }
+    pub fn new(hash_mb: usize) -> Self {
+        Self { engine: Engine::with_hash_size(hash_mb) }
     }
 
     pub fn set_position(&mut self, args: &str) {
@@ -1,5 +1,6 @@
 use crate::core::{position::Position, zobrist::ZobristHash};
 
+#[derive(Clone)]
 pub struct GameState {
     pub pos: Position,
     pub zobrist_stack: Vec<ZobristHash>,
@@ -0,0 +1,43 @@
+use crate::core::position::Position;
+use crate::core::score::{PackedScore, s};
+use crate::core::types::{Color, Piece, PieceType};
+
+// Pawns and rooks gain value as the board empties, knights lose some
+pub const PAWN_VALUE: PackedScore = s(100, 120);
+pub const KNIGHT_VALUE: PackedScore = s(300, 290);
+pub const BISHOP_VALUE: PackedScore = s(320, 320);
+pub const ROOK_VALUE: PackedScore = s(500, 540);
+pub const QUEEN_VALUE: PackedScore = s(900, 960);
+
+const PIECE_VALUES: [PackedScore; 6] = [
+    PAWN_VALUE,
+    KNIGHT_VALUE,
+    BISHOP_VALUE,
+    ROOK_VALUE,
+    QUEEN_VALUE,
+    s(20000, 20000), // King
+];
+
+/// Middlegame value of a piece, used by move ordering and pruning
+pub fn get_piece_value(piece_type: PieceType) -> i16 {
+    debug_assert!(piece_type != PieceType::NONE, "Piece must not be NONE");
+    PIECE_VALUES[piece_type.as_u8() as usize].mg()
+}
+
+/// Middlegame and endgame value of a piece
+pub fn get_piece_material(piece_type: PieceType) -> PackedScore {
+    debug_assert!(piece_type != PieceType::KING, "The king has no material value");
+    PIECE_VALUES[piece_type.as_usize()]
+}
+
+/// Material of one side, `Position` keeps it up to date incrementally
+pub fn evaluate_material(pos: &Position, color: Color) -> PackedScore {
+    let mut material = PackedScore::ZERO;
+    for piece_type in
+        [PieceType::PAWN, PieceType::KNIGHT, PieceType::BISHOP, PieceType::ROOK, PieceType::QUEEN]
+    {
+        let count = pos.bitboards[Piece::get_piece(color, piece_type).as_usize()].count();
+        material += get_piece_material(piece_type) * count as i32;
+    }
+    material
+}
@@ -1,6 +1,9 @@
 pub mod game_state;
 pub mod magic;
+pub mod material;
 pub mod move_gen;
+pub mod piece_square_table;
 pub mod position;
+pub mod score;
 pub mod types;
 pub mod zobrist;
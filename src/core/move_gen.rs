@@ -427,6 +427,32 @@ pub fn calc_attack_map_and_checker<const COLOR: u8>(pos: &mut Position) -> (BitB
     (final_mask, checkers)
 }
 
+/// Returns the pieces of both colors attacking `sq`, sliding attacks are computed with the given
+/// occupancy so that x-ray attackers are revealed when pieces are removed from it
+pub fn attackers_to(pos: &Position, sq: Square, occupancy: BitBoard) -> BitBoard {
+    let bb = &pos.bitboards;
+    let sq_mask = sq.to_bitboard().get();
+
+    // a white pawn attacks `sq` from one rank below, a black pawn from one rank above
+    let white_pawn_sources =
+        ((sq_mask >> 7) & !BitBoard::MASK_A) | ((sq_mask >> 9) & !BitBoard::MASK_H);
+    let black_pawn_sources =
+        ((sq_mask << 7) & !BitBoard::MASK_H) | ((sq_mask << 9) & !BitBoard::MASK_A);
+
+    let knights = bb[Piece::W_KNIGHT.as_usize()] | bb[Piece::B_KNIGHT.as_usize()];
+    let kings = bb[Piece::W_KING.as_usize()] | bb[Piece::B_KING.as_usize()];
+    let queens = bb[Piece::W_QUEEN.as_usize()] | bb[Piece::B_QUEEN.as_usize()];
+    let bishops = bb[Piece::W_BISHOP.as_usize()] | bb[Piece::B_BISHOP.as_usize()] | queens;
+    let rooks = bb[Piece::W_ROOK.as_usize()] | bb[Piece::B_ROOK.as_usize()] | queens;
+
+    (BitBoard::from(white_pawn_sources) & bb[Piece::W_PAWN.as_usize()])
+        | (BitBoard::from(black_pawn_sources) & bb[Piece::B_PAWN.as_usize()])
+        | (KNIGHT_MASKS[sq.as_usize()] & knights)
+        | (KING_MASKS[sq.as_usize()] & kings)
+        | (get_bishop_attack_mask(occupancy, sq) & bishops)
+        | (get_rook_attack_mask(occupancy, sq) & rooks)
+}
+
 /// Precomputes the move masks
 const fn build_pawn_attack_mask<const IS_WHITE: bool>(file: u8, rank: u8) -> BitBoard {
     let mut mask = BitBoard::new();
@@ -544,7 +570,7 @@ pub const PAWN_EN_PASSANT_MASKS: [[BitBoard; 64]; 2] = {
     masks
 };
 
-const KNIGHT_MASKS: [BitBoard; 64] = {
+pub const KNIGHT_MASKS: [BitBoard; 64] = {
     let mut masks = [BitBoard::new(); 64];
     let mut sq = 0;
     while sq < 64 {
@@ -556,7 +582,7 @@ const KNIGHT_MASKS: [BitBoard; 64] = {
     masks
 };
 
-const KING_MASKS: [BitBoard; 64] = {
+pub const KING_MASKS: [BitBoard; 64] = {
     let mut masks = [BitBoard::new(); 64];
     let mut sq = 0;
     while sq < 64 {
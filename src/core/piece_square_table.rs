@@ -0,0 +1,260 @@
+use super::score::PackedScore;
+use crate::core::position::Position;
+use crate::core::types::{Color, Piece, PieceType, Square};
+
+#[rustfmt::skip]
+pub const PST_PAWN_START: [i16; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0
+];
+
+#[rustfmt::skip]
+pub const PST_PAWN_END: [i16; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    20,  20,  20,  20,  20,  20,  20,  20,
+    10,  10,  10,  10,  10,  10,  10,  10,
+    10,  10,  10,  10,  10,  10,  10,  10,
+     0,   0,   0,   0,   0,   0,   0,   0
+];
+
+#[rustfmt::skip]
+pub const PST_KNIGHT_START: [i16; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+pub const PST_KNIGHT_END: [i16; 64] = [
+    -50,-40,-30,-25,-25,-30,-40,-50,
+    -35,-20,-10, -5, -5,-10,-20,-35,
+    -25,-10,  5, 10, 10,  5,-10,-25,
+    -20, -5, 10, 20, 20, 10, -5,-20,
+    -20, -5, 10, 20, 20, 10, -5,-20,
+    -25,-10,  5, 10, 10,  5,-10,-25,
+    -35,-20,-10, -5, -5,-10,-20,-35,
+    -50,-40,-30,-25,-25,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+pub const PST_BISHOP_START: [i16; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+pub const PST_BISHOP_END: [i16; 64] = [
+    -15,-10, -8, -5, -5, -8,-10,-15,
+    -10, -5,  0,  2,  2,  0, -5,-10,
+     -8,  0,  5,  8,  8,  5,  0, -8,
+     -5,  2,  8, 12, 12,  8,  2, -5,
+     -5,  2,  8, 12, 12,  8,  2, -5,
+     -8,  0,  5,  8,  8,  5,  0, -8,
+    -10, -5,  0,  2,  2,  0, -5,-10,
+    -15,-10, -8, -5, -5, -8,-10,-15,
+];
+
+#[rustfmt::skip]
+pub const PST_ROOK_START: [i16; 64] = [
+    0,  0,  0,  0,  0,  0,  0,  0,
+    5, 10, 10, 10, 10, 10, 10,  5,
+   -5,  0,  0,  0,  0,  0,  0, -5,
+   -5,  0,  0,  0,  0,  0,  0, -5,
+   -5,  0,  0,  0,  0,  0,  0, -5,
+   -5,  0,  0,  0,  0,  0,  0, -5,
+   -5,  0,  0,  0,  0,  0,  0, -5,
+    0,  0,  0,  5,  5,  0,  0,  0
+];
+
+#[rustfmt::skip]
+pub const PST_ROOK_END: [i16; 64] = [
+    5,  5,  5,  5,  5,  5,  5,  5,
+   15, 15, 15, 15, 15, 15, 15, 15,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+    0,  0,  0,  0,  0,  0,  0,  0,
+   -5, -5, -5, -5, -5, -5, -5, -5,
+   -5, -5, -5, -5, -5, -5, -5, -5,
+];
+
+#[rustfmt::skip]
+pub const PST_QUEEN_START: [i16; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20
+];
+
+#[rustfmt::skip]
+pub const PST_QUEEN_END: [i16; 64] = [
+    -30,-20,-15,-10,-10,-15,-20,-30,
+    -20,-10, -5,  0,  0, -5,-10,-20,
+    -15, -5, 10, 15, 15, 10, -5,-15,
+    -10,  0, 15, 25, 25, 15,  0,-10,
+    -10,  0, 15, 25, 25, 15,  0,-10,
+    -15, -5, 10, 15, 15, 10, -5,-15,
+    -20,-10, -5,  0,  0, -5,-10,-20,
+    -30,-20,-15,-10,-10,-15,-20,-30,
+];
+
+#[rustfmt::skip]
+pub const PST_KING_START: [i16; 64] = [
+    -80, -70, -70, -70, -70, -70, -70, -80,
+    -60, -60, -60, -60, -60, -60, -60, -60,
+    -40, -50, -50, -60, -60, -50, -50, -40,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,  -5,  -5,  -5,  -5,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20
+];
+
+#[rustfmt::skip]
+pub const PST_KING_END: [i16; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+    -10,  -5,  20,  30,  30,  20,  -5, -10,
+    -15, -10,  35,  45,  45,  35, -10, -15,
+    -20, -15,  30,  40,  40,  30, -15, -20,
+    -25, -20,  20,  25,  25,  20, -20, -25,
+    -30, -25,   0,   0,   0,   0, -25, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50
+];
+
+const fn flip_table(input: &[i16; 64]) -> [i16; 64] {
+    let mut output = [0; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let mirror = 56 ^ sq;
+        output[sq] = input[mirror];
+        sq += 1
+    }
+    output
+}
+
+const fn pack_table(mg: &[i16; 64], eg: &[i16; 64]) -> [PackedScore; 64] {
+    let mut output = [PackedScore::ZERO; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        output[sq] = PackedScore::new(mg[sq], eg[sq]);
+        sq += 1
+    }
+    output
+}
+
+/// Packs a middlegame and an endgame table, and mirrors them for black
+const fn make_tables(mg: &[i16; 64], eg: &[i16; 64]) -> PieceSquareTable {
+    [pack_table(mg, eg), pack_table(&flip_table(mg), &flip_table(eg))]
+}
+
+pub type PieceSquareTable = [[PackedScore; 64]; 2];
+
+pub const PAWN_TABLES: PieceSquareTable = make_tables(&PST_PAWN_START, &PST_PAWN_END);
+pub const KNIGHT_TABLES: PieceSquareTable = make_tables(&PST_KNIGHT_START, &PST_KNIGHT_END);
+pub const BISHOP_TABLES: PieceSquareTable = make_tables(&PST_BISHOP_START, &PST_BISHOP_END);
+pub const ROOK_TABLES: PieceSquareTable = make_tables(&PST_ROOK_START, &PST_ROOK_END);
+pub const QUEEN_TABLES: PieceSquareTable = make_tables(&PST_QUEEN_START, &PST_QUEEN_END);
+pub const KING_TABLES: PieceSquareTable = make_tables(&PST_KING_START, &PST_KING_END);
+
+// Indexed by piece type
+const PIECE_SQUARE_TABLES: [PieceSquareTable; 6] =
+    [PAWN_TABLES, KNIGHT_TABLES, BISHOP_TABLES, ROOK_TABLES, QUEEN_TABLES, KING_TABLES];
+
+/// Score of a piece standing on a square
+pub fn piece_square_score(piece: Piece, sq: Square) -> PackedScore {
+    PIECE_SQUARE_TABLES[piece.get_type().as_usize()][piece.color().as_usize()][sq.as_usize()]
+}
+
+/// Sum of the piece-square scores of all the pieces of one side, `Position` keeps it up to date
+/// incrementally
+pub fn evaluate_piece_squares(pos: &Position, color: Color) -> PackedScore {
+    let mut value = PackedScore::ZERO;
+    value += evaluate_table(pos, &PAWN_TABLES, PieceType::PAWN, color);
+    value += evaluate_table(pos, &KNIGHT_TABLES, PieceType::KNIGHT, color);
+    value += evaluate_table(pos, &BISHOP_TABLES, PieceType::BISHOP, color);
+    value += evaluate_table(pos, &ROOK_TABLES, PieceType::ROOK, color);
+    value += evaluate_table(pos, &QUEEN_TABLES, PieceType::QUEEN, color);
+    value += evaluate_table(pos, &KING_TABLES, PieceType::KING, color);
+    value
+}
+
+pub fn evaluate_table(
+    pos: &Position,
+    piece_square_table: &PieceSquareTable,
+    piece_type: PieceType,
+    color: Color,
+) -> PackedScore {
+    let piece = Piece::get_piece(color, piece_type);
+    let bitboard = pos.bitboards[piece.as_usize()];
+
+    let mut score = PackedScore::ZERO;
+    for sq in bitboard.iter() {
+        score += piece_square_table[color.as_usize()][sq.as_u8() as usize];
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_table_helper(table1: &[PackedScore; 64], table2: &[PackedScore; 64]) {
+        for f in 0..8 {
+            for r in 0..8 {
+                let r1 = r;
+                let r2 = 7 - r;
+                assert_eq!(table1[f + r1 * 8 as usize], table2[f + r2 * 8 as usize]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_table_mirrored() {
+        test_table_helper(&PAWN_TABLES[0], &PAWN_TABLES[1]);
+        test_table_helper(&KNIGHT_TABLES[0], &KNIGHT_TABLES[1]);
+        test_table_helper(&BISHOP_TABLES[0], &BISHOP_TABLES[1]);
+        test_table_helper(&ROOK_TABLES[0], &ROOK_TABLES[1]);
+        test_table_helper(&QUEEN_TABLES[0], &QUEEN_TABLES[1]);
+        test_table_helper(&KING_TABLES[0], &KING_TABLES[1]);
+    }
+
+    #[test]
+    fn test_evaluate_table() {
+        let pos = Position::new();
+        let score = evaluate_table(&pos, &KNIGHT_TABLES, PieceType::KNIGHT, Color::WHITE);
+        assert_eq!(score.mg(), -80, "Initial position should have a score of -40 for each knights");
+        let score = evaluate_table(&pos, &KNIGHT_TABLES, PieceType::KNIGHT, Color::BLACK);
+        assert_eq!(score.mg(), -80, "Initial position should have a score of -40 for each knights");
+
+        let score = evaluate_table(&pos, &KING_TABLES, PieceType::KING, Color::WHITE);
+        assert_eq!((score.mg(), score.eg()), (-70, -10), "King tables should differ by phase");
+        let score = evaluate_table(&pos, &ROOK_TABLES, PieceType::ROOK, Color::WHITE);
+        assert_eq!((score.mg(), score.eg()), (0, 10), "Rook tables should differ by phase");
+    }
+}
@@ -1,4 +1,7 @@
+use crate::core::material::evaluate_material;
 use crate::core::move_gen;
+use crate::core::piece_square_table::evaluate_piece_squares;
+use crate::core::score::PackedScore;
 use crate::core::zobrist::*;
 
 use super::types::*;
@@ -52,6 +55,14 @@ pub struct UndoState {
     pub checkers: [CheckerList; Color::COUNT],
 
     pub king_squares: [Square; Color::COUNT],
+
+    /// Updated incrementally by make_move, restored with the rest of the state by unmake_move
+    pub zobrist: ZobristHash,
+    pub pawn_key: ZobristHash,
+    pub material_key: ZobristHash,
+    pub non_pawn_keys: [ZobristHash; Color::COUNT],
+    pub material: [PackedScore; Color::COUNT],
+    pub piece_squares: [PackedScore; Color::COUNT],
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -102,10 +113,27 @@ impl Position {
             attack_mask: [BitBoard::new(); Color::COUNT],
             checkers: [CheckerList::new(); Color::COUNT],
             king_squares: [Square::NONE; Color::COUNT],
+            zobrist: ZobristHash::null(),
+            pawn_key: ZobristHash::null(),
+            material_key: ZobristHash::null(),
+            non_pawn_keys: [ZobristHash::null(); Color::COUNT],
+            material: [PackedScore::ZERO; Color::COUNT],
+            piece_squares: [PackedScore::ZERO; Color::COUNT],
         };
 
         let mut pos = Position { bitboards, side_to_move, state };
         internal::update_cache(&mut pos);
+        pos.state.zobrist = zobrist_hash(&pos);
+        pos.state.pawn_key = pawn_hash(&pos);
+        pos.state.material_key = material_hash(&pos);
+        pos.state.non_pawn_keys =
+            [non_pawn_hash(&pos, Color::WHITE), non_pawn_hash(&pos, Color::BLACK)];
+        pos.state.material =
+            [evaluate_material(&pos, Color::WHITE), evaluate_material(&pos, Color::BLACK)];
+        pos.state.piece_squares = [
+            evaluate_piece_squares(&pos, Color::WHITE),
+            evaluate_piece_squares(&pos, Color::BLACK),
+        ];
 
         Ok(pos)
     }
@@ -126,7 +154,28 @@ impl Position {
     }
 
     pub fn zobrist(&self) -> ZobristHash {
-        zobrist_hash(&self)
+        self.state.zobrist
+    }
+
+    pub fn pawn_key(&self) -> ZobristHash {
+        self.state.pawn_key
+    }
+
+    pub fn material_key(&self) -> ZobristHash {
+        self.state.material_key
+    }
+
+    pub fn non_pawn_key(&self, color: Color) -> ZobristHash {
+        self.state.non_pawn_keys[color.as_usize()]
+    }
+
+    /// Material of one side, kings excluded
+    pub fn material(&self, color: Color) -> PackedScore {
+        self.state.material[color.as_usize()]
+    }
+
+    pub fn piece_square_score(&self, color: Color) -> PackedScore {
+        self.state.piece_squares[color.as_usize()]
     }
 
     pub fn white_to_move(&self) -> bool {
@@ -322,4 +371,53 @@ mod tests {
         assert_eq!(pos.get_piece_at(Square::C2), Piece::B_PAWN);
         assert_eq!(pos.get_piece_at(Square::C1), Piece::NONE);
     }
+
+    fn assert_state_in_sync(pos: &Position, context: &str) {
+        assert_eq!(pos.zobrist(), zobrist_hash(pos), "Key out of sync {}", context);
+        assert_eq!(pos.pawn_key(), pawn_hash(pos), "Pawn key out of sync {}", context);
+        assert_eq!(pos.material_key(), material_hash(pos), "Material key out of sync {}", context);
+        for color in [Color::WHITE, Color::BLACK] {
+            assert_eq!(
+                pos.non_pawn_key(color),
+                non_pawn_hash(pos, color),
+                "Non-pawn key out of sync {}",
+                context
+            );
+            assert_eq!(
+                pos.material(color),
+                evaluate_material(pos, color),
+                "Material out of sync {}",
+                context
+            );
+            assert_eq!(
+                pos.piece_square_score(color),
+                evaluate_piece_squares(pos, color),
+                "Piece-square score out of sync {}",
+                context
+            );
+        }
+    }
+
+    #[test]
+    fn keys_and_scores_should_be_updated_incrementally() {
+        let mut pos = Position::from_fen(UNDO_TEST_FEN).unwrap();
+        let moves = [
+            Move::new(Square::B7, Square::B5, MoveType::Normal, None),
+            Move::new(Square::A5, Square::B6, MoveType::EnPassant, None),
+            Move::new(Square::E8, Square::G8, MoveType::Castling, None),
+            Move::new(Square::B6, Square::B7, MoveType::Normal, None),
+            Move::new(Square::C2, Square::C1, MoveType::Promotion, Some(PieceType::QUEEN)),
+        ];
+
+        let mut undo_states = Vec::new();
+        for mv in moves {
+            undo_states.push(pos.make_move(mv).0);
+            assert_state_in_sync(&pos, &format!("after {}", mv.to_string()));
+        }
+        for (mv, undo_state) in moves.iter().zip(undo_states.iter()).rev() {
+            pos.unmake_move(*mv, undo_state);
+            assert_state_in_sync(&pos, &format!("after undoing {}", mv.to_string()));
+        }
+        assert_eq!(pos.zobrist(), Position::from_fen(UNDO_TEST_FEN).unwrap().zobrist());
+    }
 }
@@ -1,6 +1,9 @@
 use super::UndoState;
+use crate::core::material::{evaluate_material, get_piece_material};
 use crate::core::move_gen::PAWN_EN_PASSANT_MASKS;
+use crate::core::piece_square_table::{evaluate_piece_squares, piece_square_score};
 use crate::core::position::*;
+use crate::core::zobrist;
 
 // Assume passed in moves are legal
 pub fn make_move(_pos: &mut Position, mv: Move) -> (UndoState, bool) {
@@ -51,11 +54,17 @@ pub fn make_move(_pos: &mut Position, mv: Move) -> (UndoState, bool) {
 
     debug_assert!(pos.state.occupancies[pos.side_to_move.as_usize()].test(src_sq.as_u8()));
 
+    toggle_piece(&mut pos.state, src_piece, src_sq);
+    toggle_piece(&mut pos.state, src_piece, dst_sq);
+    move_score(&mut pos.state, src_piece, src_sq, dst_sq);
     move_piece(&mut pos.bitboards[src_piece_idx], src_sq, dst_sq);
 
     let captured_something = if dst_piece != Piece::NONE {
         // Clear the 'to' square for the captured piece
         pos.bitboards[dst_piece.as_usize()].unset(dst_sq.as_u8());
+        toggle_piece(&mut pos.state, dst_piece, dst_sq);
+        toggle_material(&mut pos.state, dst_piece, pos.bitboards[dst_piece.as_usize()].count());
+        remove_score(&mut pos.state, dst_piece, dst_sq);
         true
     } else {
         false
@@ -74,12 +83,25 @@ pub fn make_move(_pos: &mut Position, mv: Move) -> (UndoState, bool) {
             // move rook position
             let (piece, src_sq, to_sq) = CASTLING_ROOK_SQUARES[index as usize];
             move_piece(&mut pos.bitboards[piece.as_usize()], src_sq, to_sq);
+            toggle_piece(&mut pos.state, piece, src_sq);
+            toggle_piece(&mut pos.state, piece, to_sq);
+            move_score(&mut pos.state, piece, src_sq, to_sq);
         }
         MoveType::Promotion => {
             debug_assert!(src_piece_type == PieceType::PAWN);
             let promotion = Piece::get_piece(mover_color, mv.get_promotion().unwrap());
             pos.bitboards[src_piece_idx].unset(dst_sq.as_u8()); // Remove the pawn from the board
             pos.bitboards[promotion.as_usize()].set(dst_sq.as_u8()); // Place the promoted piece on the board
+            toggle_piece(&mut pos.state, src_piece, dst_sq);
+            toggle_piece(&mut pos.state, promotion, dst_sq);
+            toggle_material(&mut pos.state, src_piece, pos.bitboards[src_piece_idx].count());
+            toggle_material(
+                &mut pos.state,
+                promotion,
+                pos.bitboards[promotion.as_usize()].count() - 1,
+            );
+            remove_score(&mut pos.state, src_piece, dst_sq);
+            add_score(&mut pos.state, promotion, dst_sq);
         }
         MoveType::EnPassant => {
             debug_assert!(src_piece_type == PieceType::PAWN, "En passant must be a pawn move");
@@ -87,14 +109,24 @@ pub fn make_move(_pos: &mut Position, mv: Move) -> (UndoState, bool) {
             let enemy = Piece::get_piece(enemy_color, PieceType::PAWN);
 
             pos.bitboards[enemy.as_usize()].unset(enemy_sq.as_u8());
+            toggle_piece(&mut pos.state, enemy, enemy_sq);
+            toggle_material(&mut pos.state, enemy, pos.bitboards[enemy.as_usize()].count());
+            remove_score(&mut pos.state, enemy, enemy_sq);
         }
         _ => {}
     }
 
     // -------------- Update Board End --------------
 
+    let mut key = pos.state.zobrist.0;
+    key ^= zobrist::side_to_move_key();
+    key ^=
+        zobrist::castling_key(pos.state.castling_rights) ^ zobrist::castling_key(castling_rights);
+    key ^= zobrist::en_passant_key(pos.state.en_passant) ^ zobrist::en_passant_key(en_passant_sq);
+
     pos.side_to_move = pos.side_to_move.flip();
 
+    pos.state.zobrist = ZobristHash(key);
     pos.state.castling_rights = castling_rights;
     pos.state.en_passant = en_passant_sq;
     pos.state.fullmove_number += if mover_color == Color::WHITE { 0 } else { 1 };
@@ -105,6 +137,29 @@ pub fn make_move(_pos: &mut Position, mv: Move) -> (UndoState, bool) {
         pos.state.halfmove_clock += 1; // increment halfmove clock for a pawn move
     }
 
+    debug_assert_eq!(pos.state.zobrist, zobrist::zobrist_hash(pos), "Zobrist key out of sync");
+    debug_assert_eq!(pos.state.pawn_key, zobrist::pawn_hash(pos), "Pawn key out of sync");
+    debug_assert_eq!(
+        pos.state.material_key,
+        zobrist::material_hash(pos),
+        "Material key out of sync"
+    );
+    debug_assert_eq!(
+        pos.state.non_pawn_keys,
+        [zobrist::non_pawn_hash(pos, Color::WHITE), zobrist::non_pawn_hash(pos, Color::BLACK)],
+        "Non-pawn keys out of sync"
+    );
+    debug_assert_eq!(
+        pos.state.material,
+        [evaluate_material(pos, Color::WHITE), evaluate_material(pos, Color::BLACK)],
+        "Material out of sync"
+    );
+    debug_assert_eq!(
+        pos.state.piece_squares,
+        [evaluate_piece_squares(pos, Color::WHITE), evaluate_piece_squares(pos, Color::BLACK)],
+        "Piece-square scores out of sync"
+    );
+
     let ok = update_cache(pos);
     (undo_state, ok)
 }
@@ -204,6 +259,42 @@ pub fn update_cache(pos: &mut Position) -> bool {
     true
 }
 
+/// Adds or removes a piece on a square from the keys
+fn toggle_piece(state: &mut UndoState, piece: Piece, sq: Square) {
+    let key = zobrist::piece_key(piece, sq);
+    state.zobrist.0 ^= key;
+    if piece.get_type() == PieceType::PAWN {
+        state.pawn_key.0 ^= key;
+    } else {
+        state.non_pawn_keys[piece.color().as_usize()].0 ^= key;
+    }
+}
+
+/// Updates the piece-square score of a piece moving between two squares
+fn move_score(state: &mut UndoState, piece: Piece, from_sq: Square, to_sq: Square) {
+    state.piece_squares[piece.color().as_usize()] +=
+        piece_square_score(piece, to_sq) - piece_square_score(piece, from_sq);
+}
+
+/// Removes the material and piece-square score of a piece leaving the board
+fn remove_score(state: &mut UndoState, piece: Piece, sq: Square) {
+    let c = piece.color().as_usize();
+    state.material[c] -= get_piece_material(piece.get_type());
+    state.piece_squares[c] -= piece_square_score(piece, sq);
+}
+
+/// Adds the material and piece-square score of a piece put on the board
+fn add_score(state: &mut UndoState, piece: Piece, sq: Square) {
+    let c = piece.color().as_usize();
+    state.material[c] += get_piece_material(piece.get_type());
+    state.piece_squares[c] += piece_square_score(piece, sq);
+}
+
+/// Adds or removes the `nth` (0-based) piece of a kind from the material key
+fn toggle_material(state: &mut UndoState, piece: Piece, nth: u32) {
+    state.material_key.0 ^= zobrist::material_key(piece, nth);
+}
+
 fn move_piece(board: &mut BitBoard, from_sq: Square, to_sq: Square) {
     debug_assert!(board.test(from_sq.as_u8()), "No piece found on 'from' square");
     board.unset(from_sq.as_u8());
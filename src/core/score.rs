@@ -0,0 +1,121 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A middlegame and an endgame score packed in a single integer, so both phases are summed with
+/// one addition. The endgame score lives in the upper 16 bits and the middlegame score in the
+/// lower 16 bits, a negative middlegame score borrows from the endgame half and is given back by
+/// `eg()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PackedScore(i32);
+
+/// Shorthand for `PackedScore::new`, used by the evaluation tables
+pub const fn s(mg: i16, eg: i16) -> PackedScore {
+    PackedScore::new(mg, eg)
+}
+
+impl PackedScore {
+    pub const ZERO: Self = Self(0);
+
+    /// Game phase of the starting position, phase 0 is a pawn (or bare king) endgame
+    pub const MAX_PHASE: i32 = 24;
+
+    pub const fn new(mg: i16, eg: i16) -> Self {
+        Self(((eg as i32) << 16).wrapping_add(mg as i32))
+    }
+
+    pub const fn mg(&self) -> i16 {
+        self.0 as i16
+    }
+
+    pub const fn eg(&self) -> i16 {
+        ((self.0.wrapping_add(0x8000)) >> 16) as i16
+    }
+
+    /// Interpolates between the middlegame and the endgame score, `phase` goes from 0 (endgame)
+    /// to `MAX_PHASE` (middlegame)
+    pub const fn taper(&self, phase: i32) -> i32 {
+        debug_assert!(phase >= 0 && phase <= Self::MAX_PHASE);
+        (self.mg() as i32 * phase + self.eg() as i32 * (Self::MAX_PHASE - phase)) / Self::MAX_PHASE
+    }
+}
+
+impl Add for PackedScore {
+    type Output = Self;
+
+    #[inline(always)]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl AddAssign for PackedScore {
+    #[inline(always)]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for PackedScore {
+    type Output = Self;
+
+    #[inline(always)]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl SubAssign for PackedScore {
+    #[inline(always)]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for PackedScore {
+    type Output = Self;
+
+    #[inline(always)]
+    fn neg(self) -> Self {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+impl Mul<i32> for PackedScore {
+    type Output = Self;
+
+    #[inline(always)]
+    fn mul(self, rhs: i32) -> Self {
+        Self(self.0.wrapping_mul(rhs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packing() {
+        for (mg, eg) in [(0, 0), (10, -20), (-35, 40), (-1, -1), (20000, 20000), (-300, 900)] {
+            let score = s(mg, eg);
+            assert_eq!((score.mg(), score.eg()), (mg, eg));
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = s(-30, 50);
+        let b = s(45, -70);
+        assert_eq!(a + b, s(15, -20));
+        assert_eq!(a - b, s(-75, 120));
+        assert_eq!(-a, s(30, -50));
+        assert_eq!(b * 3, s(135, -210));
+        assert_eq!(a * -2, s(60, -100));
+    }
+
+    #[test]
+    fn test_taper() {
+        let score = s(100, 200);
+        assert_eq!(score.taper(PackedScore::MAX_PHASE), 100);
+        assert_eq!(score.taper(0), 200);
+        assert_eq!(score.taper(PackedScore::MAX_PHASE / 2), 150);
+    }
+}
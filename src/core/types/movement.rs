@@ -90,6 +90,15 @@ impl Move {
         self.0 == 0
     }
 
+    /// Raw packed representation, see the bit layout above
+    pub const fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    pub const fn from_u16(data: u16) -> Self {
+        Self(data)
+    }
+
     pub fn src_sq(&self) -> Square {
         Square::new((self.0 & Self::SQUARE_MASK) as u8)
     }
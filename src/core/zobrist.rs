@@ -1,6 +1,6 @@
 /// The implementation are copied from https://github.com/shaack/cm-polyglot/blob/main/lib/stakelbase/KeyGenerator.js
 use crate::core::position::Position;
-use crate::core::types::{CastlingRight, Color, Piece, Square};
+use crate::core::types::{CastlingRight, Color, Piece, PieceType, Square};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ZobristHash(pub u64);
@@ -22,6 +22,74 @@ pub fn zobrist_hash(pos: &Position) -> ZobristHash {
     ZobristHash(weed)
 }
 
+/// Hash of the pawns of both sides, used to cache pawn structure terms
+pub fn pawn_hash(pos: &Position) -> ZobristHash {
+    let mut weed: u64 = 0;
+    for piece in [Piece::W_PAWN, Piece::B_PAWN] {
+        for sq in pos.bitboards[piece.as_usize()].iter() {
+            weed ^= piece_key(piece, sq);
+        }
+    }
+    ZobristHash(weed)
+}
+
+/// Hash of the number of pieces of each kind, regardless of where they stand
+pub fn material_hash(pos: &Position) -> ZobristHash {
+    let mut weed: u64 = 0;
+    for i in 0..Piece::COUNT {
+        let piece = Piece::new(i as u8);
+        for nth in 0..pos.bitboards[i].count() {
+            weed ^= material_key(piece, nth);
+        }
+    }
+    ZobristHash(weed)
+}
+
+/// Hash of the pieces other than pawns (king included) of one side
+pub fn non_pawn_hash(pos: &Position, color: Color) -> ZobristHash {
+    let mut weed: u64 = 0;
+    for i in 0..Piece::COUNT {
+        let piece = Piece::new(i as u8);
+        if piece.color() != color || piece.get_type() == PieceType::PAWN {
+            continue;
+        }
+        for sq in pos.bitboards[i].iter() {
+            weed ^= piece_key(piece, sq);
+        }
+    }
+    ZobristHash(weed)
+}
+
+/// Key of the `nth` (0-based) piece of a kind in the material hash, the piece keys of the first
+/// squares are reused as there can't be more than 64 pieces of a kind
+pub fn material_key(piece: Piece, nth: u32) -> u64 {
+    debug_assert!(nth < 64);
+    piece_key(piece, Square::new(nth as u8))
+}
+
+/// Key of a piece standing on a square
+pub fn piece_key(piece: Piece, sq: Square) -> u64 {
+    RANDOM_HASHES[64 * PIECE_HASH_MULTIPLIER[piece.as_usize()] + sq.as_u8() as usize]
+}
+
+/// Key of an en passant square, only the file is hashed
+pub fn en_passant_key(en_passant_sq: Option<Square>) -> u64 {
+    let mut weed = 0;
+    hash_en_passant(&mut weed, en_passant_sq);
+    weed
+}
+
+/// Key toggled every time the side to move changes
+pub fn side_to_move_key() -> u64 {
+    RANDOM_HASHES[780]
+}
+
+pub fn castling_key(castling_rights: u8) -> u64 {
+    let mut weed = 0;
+    hash_castling_rights(&mut weed, castling_rights);
+    weed
+}
+
 fn xor_with_random(weed: &mut u64, num: usize) {
     *weed = *weed ^ RANDOM_HASHES[num];
 }
@@ -96,6 +164,24 @@ mod tests {
 
         assert_eq!(zobrist_hash(&pos), ZobristHash(5060803636482931868)); // Initial position hash
     }
+
+    #[test]
+    fn test_material_hash_ignores_squares() {
+        let pos1 = Position::from_fen("4k3/2p5/8/8/8/8/3N4/4K3 w - - 0 1").unwrap();
+        let pos2 = Position::from_fen("4k3/8/8/5p2/8/1N6/8/3K4 b - - 0 1").unwrap();
+        let pos3 = Position::from_fen("4k3/2p5/8/8/8/8/3B4/4K3 w - - 0 1").unwrap();
+        assert_eq!(material_hash(&pos1), material_hash(&pos2));
+        assert_ne!(material_hash(&pos1), material_hash(&pos3));
+    }
+
+    #[test]
+    fn test_pawn_and_non_pawn_hash() {
+        let pos1 = Position::from_fen("4k3/2p5/8/8/8/8/3N4/4K3 w - - 0 1").unwrap();
+        let pos2 = Position::from_fen("3k4/2p5/8/8/8/8/8/2N1K3 b - - 0 1").unwrap();
+        assert_eq!(pawn_hash(&pos1), pawn_hash(&pos2));
+        assert_ne!(non_pawn_hash(&pos1, Color::WHITE), non_pawn_hash(&pos2, Color::WHITE));
+        assert_ne!(non_pawn_hash(&pos1, Color::BLACK), non_pawn_hash(&pos2, Color::BLACK));
+    }
 }
 
 #[rustfmt::skip]
@@ -0,0 +1,194 @@
+use crate::core::move_gen::KING_MASKS;
+use crate::core::types::*;
+use once_cell::sync::Lazy;
+
+// Every king and pawn placement with the pawn on the files a to d and the ranks 2 to 7, for both
+// sides to move
+const KPK_SIZE: usize = 2 * 24 * 64 * 64;
+
+// Results of the retrograde analysis, used as bit flags so the results of all the moves of a
+// position can be merged
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// One bit per KPK position, set when white wins
+static KPK_BITBASE: Lazy<Vec<u64>> = Lazy::new(generate_kpk);
+
+/// Runs the retrograde analysis now instead of on the first probe
+pub fn warm_up_kpk_bitbase() {
+    Lazy::force(&KPK_BITBASE);
+}
+
+/// Whether white wins the king and pawn against king ending. The squares are given with white as
+/// the strong side, positions with the pawn on the files e to h must be mirrored by the caller
+pub fn probe_kpk(
+    white_king: Square,
+    pawn: Square,
+    black_king: Square,
+    white_to_move: bool,
+) -> bool {
+    let (file, _) = pawn.file_rank();
+    debug_assert!(file.0 <= File::D.0);
+    let idx = kpk_index(white_to_move, white_king, black_king, pawn);
+    KPK_BITBASE[idx / 64] & (1u64 << (idx % 64)) != 0
+}
+
+fn kpk_index(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> usize {
+    let (file, rank) = pawn.file_rank();
+    white_king.as_usize()
+        | black_king.as_usize() << 6
+        | (!white_to_move as usize) << 12
+        | (file.0 as usize) << 13
+        | (6 - rank.0 as usize) << 15
+}
+
+/// Inverse of `kpk_index`
+fn kpk_position(idx: usize) -> (bool, Square, Square, Square) {
+    let white_king = Square::new((idx & 63) as u8);
+    let black_king = Square::new(((idx >> 6) & 63) as u8);
+    let white_to_move = (idx >> 12) & 1 == 0;
+    let pawn = Square::make(File(((idx >> 13) & 3) as u8), Rank(6 - (idx >> 15) as u8));
+    (white_to_move, white_king, black_king, pawn)
+}
+
+fn white_pawn_attacks(pawn: Square) -> BitBoard {
+    let pawn = pawn.to_bitboard().get();
+    BitBoard::from(((pawn & !BitBoard::MASK_A) << 7) | ((pawn & !BitBoard::MASK_H) << 9))
+}
+
+fn king_distance(a: Square, b: Square) -> u8 {
+    let (a_file, a_rank) = a.file_rank();
+    let (b_file, b_rank) = b.file_rank();
+    a_file.0.abs_diff(b_file.0).max(a_rank.0.abs_diff(b_rank.0))
+}
+
+/// Result of a position that doesn't depend on the other positions
+fn initial_result(idx: usize) -> u8 {
+    let (white_to_move, white_king, black_king, pawn) = kpk_position(idx);
+    let (_, rank) = pawn.file_rank();
+    let push = Square::new(pawn.as_u8() + 8);
+
+    if king_distance(white_king, black_king) <= 1
+        || white_king == pawn
+        || black_king == pawn
+        || (white_to_move && white_pawn_attacks(pawn).test_sq(black_king))
+    {
+        return INVALID;
+    }
+
+    // the pawn promotes and the new queen can't be taken
+    if white_to_move
+        && rank == Rank::_7
+        && white_king != push
+        && black_king != push
+        && (king_distance(black_king, push) > 1 || king_distance(white_king, push) == 1)
+    {
+        return WIN;
+    }
+
+    // stalemate, or the undefended pawn gets taken
+    let black_moves = KING_MASKS[black_king.as_usize()];
+    let white_guards = KING_MASKS[white_king.as_usize()];
+    if !white_to_move
+        && ((black_moves & !(white_guards | white_pawn_attacks(pawn))).none()
+            || (black_moves & !white_guards).test_sq(pawn))
+    {
+        return DRAW;
+    }
+
+    UNKNOWN
+}
+
+/// Result of a position from the results of the positions it can move to
+fn classify(results: &[u8], idx: usize) -> u8 {
+    let (white_to_move, white_king, black_king, pawn) = kpk_position(idx);
+    let (good, bad) = if white_to_move { (WIN, DRAW) } else { (DRAW, WIN) };
+
+    let mut merged = INVALID;
+    if white_to_move {
+        for sq in KING_MASKS[white_king.as_usize()].iter() {
+            merged |= results[kpk_index(false, sq, black_king, pawn)];
+        }
+
+        let (_, rank) = pawn.file_rank();
+        let push = Square::new(pawn.as_u8() + 8);
+        if rank < Rank::_7 {
+            merged |= results[kpk_index(false, white_king, black_king, push)];
+        }
+        if rank == Rank::_2 && push != white_king && push != black_king {
+            let double_push = Square::new(pawn.as_u8() + 16);
+            merged |= results[kpk_index(false, white_king, black_king, double_push)];
+        }
+    } else {
+        for sq in KING_MASKS[black_king.as_usize()].iter() {
+            merged |= results[kpk_index(true, white_king, sq, pawn)];
+        }
+    }
+
+    if merged & good != 0 {
+        good
+    } else if merged & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        bad
+    }
+}
+
+fn generate_kpk() -> Vec<u64> {
+    let mut results: Vec<u8> = (0..KPK_SIZE).map(initial_result).collect();
+
+    // positions only get resolved once all the moves of the losing side are known
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for idx in 0..KPK_SIZE {
+            if results[idx] == UNKNOWN {
+                let result = classify(&results, idx);
+                if result != UNKNOWN {
+                    results[idx] = result;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut bitbase = vec![0u64; KPK_SIZE / 64];
+    for (idx, &result) in results.iter().enumerate() {
+        if result == WIN {
+            bitbase[idx / 64] |= 1u64 << (idx % 64);
+        }
+    }
+    bitbase
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kpk_index() {
+        for idx in [0, 4095, 4096, 123_456, KPK_SIZE - 1] {
+            let (white_to_move, white_king, black_king, pawn) = kpk_position(idx);
+            assert_eq!(kpk_index(white_to_move, white_king, black_king, pawn), idx);
+        }
+    }
+
+    #[test]
+    fn test_probe_kpk() {
+        // the black king is outside the square of the pawn
+        assert!(probe_kpk(Square::D1, Square::D2, Square::H2, true));
+        // the white king in front of the pawn on the sixth rank wins whoever moves
+        assert!(probe_kpk(Square::D6, Square::D5, Square::D8, true));
+        assert!(probe_kpk(Square::D6, Square::D5, Square::D8, false));
+        // the black king blocks the pawn
+        assert!(!probe_kpk(Square::D1, Square::D2, Square::D3, true));
+        assert!(!probe_kpk(Square::D1, Square::D2, Square::D3, false));
+        // a rook pawn can't drive the king out of the corner
+        assert!(!probe_kpk(Square::B6, Square::A6, Square::A8, true));
+        // with the opposition white gets in front of the pawn
+        assert!(probe_kpk(Square::D5, Square::D4, Square::D7, false));
+        assert!(!probe_kpk(Square::D5, Square::D4, Square::D7, true));
+    }
+}
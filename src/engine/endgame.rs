@@ -0,0 +1,305 @@
+use super::bitbase::{probe_kpk, warm_up_kpk_bitbase};
+use super::evaluation::{center_distance, distance};
+use crate::core::material::get_piece_value;
+use crate::core::position::Position;
+use crate::core::types::*;
+use crate::core::zobrist::{ZobristHash, material_key};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+// Score of an endgame that is won but not yet a forced mate, far above any positional score
+const KNOWN_WIN: i32 = 10000;
+
+/// Evaluates an endgame from the point of view of the strong side, given as the second argument
+type EndgameFn = fn(&Position, Color) -> i32;
+
+pub struct Endgame {
+    pub name: &'static str,
+    strong: Color,
+    evaluate: EndgameFn,
+}
+
+impl Endgame {
+    /// Score of the position from white's point of view
+    pub fn evaluate(&self, pos: &Position) -> i32 {
+        let score = (self.evaluate)(pos, self.strong);
+        if self.strong == Color::WHITE { score } else { -score }
+    }
+}
+
+/// Specialised evaluation of the endgames the generic terms get wrong, indexed by the material
+/// key of the position
+pub struct Endgames {
+    map: HashMap<ZobristHash, Endgame>,
+}
+
+impl Endgames {
+    pub fn new() -> Self {
+        let mut endgames = Self { map: HashMap::new() };
+        endgames.add("KBNK", evaluate_kbnk);
+        endgames.add("KPK", evaluate_kpk);
+        endgames.add("KRKP", evaluate_krkp);
+        endgames.add("KQKP", evaluate_kqkp);
+        endgames.add("KQKR", evaluate_kqkr);
+        endgames.add("KRKB", evaluate_krkb);
+        endgames.add("KRKN", evaluate_krkn);
+        endgames
+    }
+
+    /// Registers an endgame given by the pieces of the strong side followed by the pieces of the
+    /// weak side, with either color as the strong side
+    fn add(&mut self, code: &'static str, evaluate: EndgameFn) {
+        for strong in [Color::WHITE, Color::BLACK] {
+            let key = material_signature(code, strong);
+            self.map.insert(key, Endgame { name: code, strong, evaluate });
+        }
+    }
+
+    pub fn probe(&self, pos: &Position) -> Option<&Endgame> {
+        self.map.get(&pos.material_key())
+    }
+}
+
+pub static ENDGAMES: Lazy<Endgames> = Lazy::new(Endgames::new);
+
+/// Builds the endgame registry and the KPK bitbase, so they aren't built by the first search that
+/// reaches one of these endgames
+pub fn warm_up_endgames() {
+    Lazy::force(&ENDGAMES);
+    warm_up_kpk_bitbase();
+}
+
+/// Material key of the pieces in `code`, like "KRKP"
+fn material_signature(code: &str, strong: Color) -> ZobristHash {
+    let weak_start = code[1..].find('K').unwrap() + 1;
+    let (strong_pieces, weak_pieces) = code.split_at(weak_start);
+
+    let mut key = 0;
+    for (pieces, color) in [(strong_pieces, strong), (weak_pieces, strong.flip())] {
+        let mut counts = [0u32; PieceType::COUNT as usize];
+        for c in pieces.chars() {
+            let piece_type = match c {
+                'P' => PieceType::PAWN,
+                'N' => PieceType::KNIGHT,
+                'B' => PieceType::BISHOP,
+                'R' => PieceType::ROOK,
+                'Q' => PieceType::QUEEN,
+                'K' => PieceType::KING,
+                _ => panic!("invalid endgame code: {}", code),
+            };
+            let piece = Piece::get_piece(color, piece_type);
+            key ^= material_key(piece, counts[piece_type.as_usize()]);
+            counts[piece_type.as_usize()] += 1;
+        }
+    }
+    ZobristHash(key)
+}
+
+/// Mirrors a square vertically when needed, so that the strong side plays up the board
+fn normalize(strong: Color, sq: Square) -> Square {
+    if strong == Color::WHITE { sq } else { Square::new(sq.as_u8() ^ 56) }
+}
+
+fn piece_square(pos: &Position, color: Color, piece_type: PieceType) -> Square {
+    let piece = Piece::get_piece(color, piece_type);
+    pos.bitboards[piece.as_usize()].iter().next().unwrap()
+}
+
+/// Bonus for keeping the kings close, needed by most mates
+fn push_close(a: Square, b: Square) -> i32 {
+    (7 - distance(a, b) as i32) * 10
+}
+
+/// Bishop and knight: mates can only be given in a corner of the bishop's color, so the weak king
+/// is driven to the closest of those
+fn evaluate_kbnk(pos: &Position, strong: Color) -> i32 {
+    let king_sq = pos.get_king_square(strong);
+    let weak_king_sq = pos.get_king_square(strong.flip());
+    let bishop_sq = piece_square(pos, strong, PieceType::BISHOP);
+
+    let (bishop_file, bishop_rank) = bishop_sq.file_rank();
+    let dark_bishop = (bishop_file.0 + bishop_rank.0) % 2 == 0;
+    let corners = if dark_bishop { [Square::A1, Square::H8] } else { [Square::A8, Square::H1] };
+    let corner_distance = corners.iter().map(|&c| distance(weak_king_sq, c)).min().unwrap();
+
+    KNOWN_WIN
+        + center_distance(weak_king_sq) as i32 * 10
+        + (7 - corner_distance as i32) * 40
+        + push_close(king_sq, weak_king_sq)
+}
+
+/// King and pawn: exact result from the bitbase
+fn evaluate_kpk(pos: &Position, strong: Color) -> i32 {
+    let mut king_sq = normalize(strong, pos.get_king_square(strong));
+    let mut weak_king_sq = normalize(strong, pos.get_king_square(strong.flip()));
+    let mut pawn_sq = normalize(strong, piece_square(pos, strong, PieceType::PAWN));
+
+    let (file, rank) = pawn_sq.file_rank();
+    if file.0 > File::D.0 {
+        king_sq = Square::new(king_sq.as_u8() ^ 7);
+        weak_king_sq = Square::new(weak_king_sq.as_u8() ^ 7);
+        pawn_sq = Square::new(pawn_sq.as_u8() ^ 7);
+    }
+
+    if probe_kpk(king_sq, pawn_sq, weak_king_sq, pos.side_to_move == strong) {
+        KNOWN_WIN + get_piece_value(PieceType::PAWN) as i32 + rank.0 as i32
+    } else {
+        0
+    }
+}
+
+/// Rook against pawn: won when the strong king stops the pawn or the weak king is too far, close
+/// to a draw when the pawn is supported by its king and far advanced
+fn evaluate_krkp(pos: &Position, strong: Color) -> i32 {
+    let weak = strong.flip();
+    let king_sq = normalize(strong, pos.get_king_square(strong));
+    let weak_king_sq = normalize(strong, pos.get_king_square(weak));
+    let rook_sq = normalize(strong, piece_square(pos, strong, PieceType::ROOK));
+    let pawn_sq = normalize(strong, piece_square(pos, weak, PieceType::PAWN));
+
+    let (pawn_file, pawn_rank) = pawn_sq.file_rank();
+    let (king_file, king_rank) = king_sq.file_rank();
+    let (_, weak_king_rank) = weak_king_sq.file_rank();
+    let queening_sq = Square::make(pawn_file, Rank::_1);
+    let push_sq = Square::new(pawn_sq.as_u8() - 8);
+    let rook_value = get_piece_value(PieceType::ROOK) as i32;
+    let weak_to_move = (pos.side_to_move == weak) as i16;
+    let strong_to_move = (pos.side_to_move == strong) as i16;
+
+    if king_file == pawn_file && king_rank < pawn_rank {
+        // the strong king is in front of the pawn
+        rook_value - distance(king_sq, pawn_sq) as i32
+    } else if distance(weak_king_sq, pawn_sq) >= 3 + weak_to_move
+        && distance(weak_king_sq, rook_sq) >= 3
+    {
+        // the weak king is too far from the pawn to protect it
+        rook_value - distance(king_sq, pawn_sq) as i32
+    } else if weak_king_rank <= Rank::_3
+        && distance(weak_king_sq, pawn_sq) == 1
+        && king_rank >= Rank::_4
+        && distance(king_sq, pawn_sq) > 2 + strong_to_move
+    {
+        // the pawn is about to promote with the help of its king
+        40 - 4 * distance(king_sq, pawn_sq) as i32
+    } else {
+        let race = distance(king_sq, push_sq)
+            - distance(weak_king_sq, push_sq)
+            - distance(pawn_sq, queening_sq);
+        100 - 4 * race as i32
+    }
+}
+
+/// Queen against pawn: won, unless a rook or bishop pawn on the seventh rank is supported by its
+/// king, where stalemate tricks hold the draw
+fn evaluate_kqkp(pos: &Position, strong: Color) -> i32 {
+    let weak = strong.flip();
+    let king_sq = normalize(strong, pos.get_king_square(strong));
+    let weak_king_sq = normalize(strong, pos.get_king_square(weak));
+    let pawn_sq = normalize(strong, piece_square(pos, weak, PieceType::PAWN));
+
+    let (pawn_file, pawn_rank) = pawn_sq.file_rank();
+    let drawish_file = [File::A, File::C, File::F, File::H].contains(&pawn_file);
+
+    let mut score = push_close(king_sq, weak_king_sq);
+    if pawn_rank != Rank::_2 || distance(weak_king_sq, pawn_sq) != 1 || !drawish_file {
+        score += get_piece_value(PieceType::QUEEN) as i32 - get_piece_value(PieceType::PAWN) as i32;
+    }
+    score
+}
+
+/// Queen against rook: won, the weak king is driven to the edge with the strong king close by
+fn evaluate_kqkr(pos: &Position, strong: Color) -> i32 {
+    let king_sq = pos.get_king_square(strong);
+    let weak_king_sq = pos.get_king_square(strong.flip());
+    get_piece_value(PieceType::QUEEN) as i32 - get_piece_value(PieceType::ROOK) as i32
+        + center_distance(weak_king_sq) as i32 * 10
+        + push_close(king_sq, weak_king_sq)
+}
+
+/// Rook against bishop: a draw in general, the weak king is still kept on the edge
+fn evaluate_krkb(pos: &Position, strong: Color) -> i32 {
+    center_distance(pos.get_king_square(strong.flip())) as i32 * 8
+}
+
+/// Rook against knight: a draw in general, unless the knight gets separated from its king
+fn evaluate_krkn(pos: &Position, strong: Color) -> i32 {
+    let weak = strong.flip();
+    let weak_king_sq = pos.get_king_square(weak);
+    let knight_sq = piece_square(pos, weak, PieceType::KNIGHT);
+    center_distance(weak_king_sq) as i32 * 8 + distance(weak_king_sq, knight_sq) as i32 * 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(fen: &str) -> Option<i32> {
+        let pos = Position::from_fen(fen).unwrap();
+        ENDGAMES.probe(&pos).map(|endgame| endgame.evaluate(&pos))
+    }
+
+    #[test]
+    fn test_probe() {
+        let pos = Position::from_fen("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1").unwrap();
+        assert_eq!(ENDGAMES.probe(&pos).map(|e| e.name), Some("KBNK"));
+        let pos = Position::from_fen("1nb1k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(ENDGAMES.probe(&pos).map(|e| e.strong), Some(Color::BLACK));
+
+        assert_eq!(evaluate("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), None);
+        assert_eq!(evaluate("4k3/8/8/8/8/8/P7/1NB1K3 w - - 0 1"), None);
+    }
+
+    #[test]
+    fn test_kbnk() {
+        // the light-squared bishop mates in a8 or h1
+        let right_corner = evaluate("k7/8/1K6/8/8/8/8/1BN5 w - - 0 1").unwrap();
+        let wrong_corner = evaluate("7k/8/6K1/8/8/8/8/1BN5 w - - 0 1").unwrap();
+        assert!(wrong_corner > KNOWN_WIN);
+        assert!(right_corner > wrong_corner);
+
+        let black = evaluate("1nb5/8/8/8/8/1k6/8/K7 w - - 0 1").unwrap();
+        assert!(black < -KNOWN_WIN);
+    }
+
+    #[test]
+    fn test_kpk() {
+        assert!(evaluate("8/8/8/8/8/8/4P2k/4K3 w - - 0 1").unwrap() > KNOWN_WIN);
+        assert_eq!(evaluate("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1"), Some(0));
+        // mirrored for black, on the h file
+        assert_eq!(evaluate("7k/7p/8/8/8/8/8/7K w - - 0 1"), Some(0));
+        assert!(evaluate("8/8/8/8/8/7p/8/1K4k1 w - - 0 1").unwrap() < -KNOWN_WIN);
+    }
+
+    #[test]
+    fn test_krkp() {
+        // the white king stops the pawn
+        let stopped = evaluate("8/8/8/8/4k3/8/3p4/3K3R w - - 0 1").unwrap();
+        // the pawn is about to promote, supported by its king
+        let supported = evaluate("8/8/1K6/8/8/8/3pk3/7R w - - 0 1").unwrap();
+        assert!(stopped > 400);
+        assert!(supported < 100);
+    }
+
+    #[test]
+    fn test_kqkp() {
+        let center_pawn = evaluate("8/8/8/8/8/1Q6/3pk3/7K w - - 0 1").unwrap();
+        // the queen can't take the pawn at once and the c pawn holds the draw by stalemate tricks
+        let bishop_pawn = evaluate("8/8/8/7Q/8/8/2pk4/7K w - - 0 1").unwrap();
+        assert!(center_pawn > 700);
+        assert!(bishop_pawn < 100);
+    }
+
+    #[test]
+    fn test_kqkr() {
+        let edge = evaluate("3k4/8/3K4/8/8/8/8/Q6r w - - 0 1").unwrap();
+        let center = evaluate("8/8/8/3k4/8/8/8/Q3K2r w - - 0 1").unwrap();
+        assert!(center > 300);
+        assert!(edge > center);
+    }
+
+    #[test]
+    fn test_krkb_krkn() {
+        assert!(evaluate("4k3/1b6/8/8/8/8/8/R3K3 w - - 0 1").unwrap() < 100);
+        assert!(evaluate("4k3/8/8/8/8/8/3n4/R3K3 w - - 0 1").unwrap() < 100);
+    }
+}
@@ -1,6 +1,11 @@
-use std::io::Write;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
 
 use crate::core::{game_state::GameState, move_gen, position::Position, types::Move};
+use crate::engine::endgame::warm_up_endgames;
+use crate::engine::evaluation::Evaluation;
+use crate::engine::pawn_table::PawnTable;
 use crate::engine::search;
 use crate::engine::ttable::TTable;
 use crate::utils;
@@ -10,9 +15,65 @@ const VERSION_MAJOR: u32 = 0;
 const VERSION_MINOR: u32 = 2;
 const VERSION_PATCH: u32 = 4; // magic bitboard
 
+// Positions searched by `Engine::bench`, the total node count is used as a search signature
+const BENCH_POSITIONS: [&str; 6] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bqk2r/pppp1ppp/2n5/2b1p3/4P1n1/3P1N2/PPPNBPPP/R1BQ1RK1 b kq - 8 6",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+];
+pub const BENCH_DEPTH: u8 = 7;
+
+/// How `negamax` makes up for a missing hash move on a TT miss
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InternalIterative {
+    Off,
+    /// Internal iterative reductions, search the node one ply shallower
+    Reduction,
+    /// Internal iterative deepening, run a shallow search first to find a move to try first
+    Deepening,
+}
+
+impl InternalIterative {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "reduction" => Some(Self::Reduction),
+            "deepening" => Some(Self::Deepening),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EngineOptions {
+    pub internal_iterative: InternalIterative,
+    /// Number of search threads, helper threads share the transposition table (Lazy SMP)
+    pub threads: usize,
+    /// File used to save and load the transposition table
+    pub hash_file: String,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            internal_iterative: InternalIterative::Reduction,
+            threads: 1,
+            hash_file: String::from("hash.bin"),
+        }
+    }
+}
+
+pub const MAX_THREADS: usize = 256;
+pub const DEFAULT_HASH_MB: usize = TTable::DEFAULT_SIZE_MB;
+pub const MAX_HASH_MB: usize = TTable::MAX_SIZE_MB;
+
 pub struct Engine {
     pub state: GameState,
-    pub(super) tt: TTable,
+    pub options: EngineOptions,
+    pub(super) tt: Arc<TTable>,
 }
 
 impl Engine {
@@ -29,18 +90,72 @@ impl Engine {
     }
 
     pub fn from_fen(fen: &str) -> Result<Self, &'static str> {
+        Self::from_fen_with_hash_size(fen, DEFAULT_HASH_MB)
+    }
+
+    /// Creates an engine at the initial position with a transposition table of `hash_mb` megabytes
+    pub fn with_hash_size(hash_mb: usize) -> Self {
+        Self::from_fen_with_hash_size(Position::DEFAULT_FEN, hash_mb).unwrap()
+    }
+
+    /// Same as `from_fen`, with a transposition table of `hash_mb` megabytes
+    pub fn from_fen_with_hash_size(fen: &str, hash_mb: usize) -> Result<Self, &'static str> {
         let state = GameState::from_fen(fen)?;
 
-        Ok(Self { state, tt: TTable::new() })
+        let tt = Arc::new(TTable::new(hash_mb));
+        Ok(Self { state, options: EngineOptions::default(), tt })
+    }
+
+    /// Builds the endgame tables now, otherwise the first search that reaches one of these
+    /// endgames pays for it
+    pub fn warm_up() {
+        warm_up_endgames();
     }
 
     pub fn reset(&mut self) {
-        *self = Self::new();
+        self.state = GameState::new();
+        self.clear();
+    }
+
+    /// Forgets the results of previous searches
+    pub fn clear(&mut self) {
+        self.tt.clear();
+    }
+
+    /// Reallocates the transposition table with the given size, dropping its content
+    pub fn set_hash_size(&mut self, hash_mb: usize) {
+        // free the old table first so both are never allocated at the same time
+        self.tt = Arc::new(TTable::new(1));
+        self.tt = Arc::new(TTable::new(hash_mb));
+    }
+
+    /// Writes the transposition table to `path`
+    pub fn save_hash(&self, path: &Path) -> io::Result<()> {
+        self.tt.save(path)
+    }
+
+    /// Replaces the transposition table with one saved by `save_hash`, the size of the table
+    /// becomes the size of the saved one
+    pub fn load_hash(&mut self, path: &Path) -> io::Result<()> {
+        self.tt = Arc::new(TTable::load(path)?);
+        Ok(())
+    }
+
+    /// Evaluates the current position, returning the value of every evaluation term
+    pub fn eval_trace(&self) -> String {
+        let mut eval = Evaluation::new();
+        eval.evaluate_position(&self.state.pos, &mut PawnTable::new());
+        eval.trace()
     }
 
     pub fn best_move(&mut self, time: f64) -> Option<Move> {
+        self.search(&mut std::io::sink(), time)
+    }
+
+    /// Same as `best_move`, but reports UCI `info` lines to the writer after every iteration
+    pub fn search<W: Write>(&mut self, writer: &mut W, time: f64) -> Option<Move> {
         let mut searcher = search::Searcher::new(time);
-        searcher.find_best_move(self)
+        searcher.find_best_move(self, writer)
     }
 
     pub fn best_move_depth(&mut self, max_depth: u8) -> Option<Move> {
@@ -48,6 +163,43 @@ impl Engine {
         searcher.find_best_move_depth(self, max_depth)
     }
 
+    /// Searches a fixed set of positions to the given depth with a fresh transposition table and
+    /// returns the total node count, which changes whenever the shape of the search tree does.
+    /// The search runs on a scratch engine, so the game and the hash of this one are left untouched
+    pub fn bench<W: Write>(&self, writer: &mut W, depth: u8) -> u64 {
+        let mut engine = Self::with_hash_size(self.tt.size_mb());
+        engine.options = self.options.clone();
+        // helper threads make the node count vary from run to run
+        engine.options.threads = 1;
+
+        let timer = utils::Timer::new();
+        let mut nodes = 0;
+        for fen in BENCH_POSITIONS {
+            engine.state = GameState::from_fen(fen).unwrap();
+            engine.clear();
+
+            let mut searcher = search::Searcher::new(f64::MAX);
+            let mv = searcher.find_best_move_depth(&mut engine, depth);
+            writeln!(
+                writer,
+                "{}: bestmove {} nodes {}",
+                fen,
+                mv.map_or("(none)".to_string(), |mv| mv.to_string()),
+                searcher.nodes()
+            )
+            .unwrap();
+            nodes += searcher.nodes();
+        }
+
+        let elapsed_ms = timer.elapsed_ms();
+        writeln!(writer, "\nNodes searched: {}", nodes).unwrap();
+        writeln!(writer, "Time: {} ms", elapsed_ms as u64).unwrap();
+        writeln!(writer, "Nodes/second: {}", (nodes as f64 * 1000.0 / elapsed_ms.max(1.0)) as u64)
+            .unwrap();
+
+        nodes
+    }
+
     pub fn apply_move_safe(&mut self, mv_str: &str) -> bool {
         let mv = utils::parse_move(mv_str);
         if mv.is_none() {
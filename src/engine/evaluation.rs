@@ -1,184 +1,428 @@
-use super::piece_square_table::*;
+use super::endgame::ENDGAMES;
+use super::pawn_table::{PawnEntry, PawnTable, pawn_attacks, stop_square};
+use crate::core::magic::{get_bishop_attack_mask, get_rook_attack_mask};
+use crate::core::material::{BISHOP_VALUE, PAWN_VALUE, ROOK_VALUE};
+use crate::core::move_gen::{KING_MASKS, KNIGHT_MASKS};
+use crate::core::score::{PackedScore, s};
 use crate::core::{position::Position, types::*};
 
-// @TODO: change to i16
-pub type Score = i16;
-
-const PAWN_VALUE: Score = 100i16;
-const KNIGHT_VALUE: Score = 300i16;
-const BISHOP_VALUE: Score = 320i16;
-const ROOK_VALUE: Score = 500i16;
-const QUEEN_VALUE: Score = 900i16;
-
-const PIECE_VALUES: [i16; 6] = [
-    PAWN_VALUE,
-    KNIGHT_VALUE,
-    BISHOP_VALUE,
-    ROOK_VALUE,
-    QUEEN_VALUE,
-    20000, // King
+// Quadratic material imbalance, by pairs of piece kinds: bishop pair, pawn, knight, bishop, rook
+// and queen. A piece kind is only paired with the kinds before it, the bishop pair on its own is
+// scored by `BISHOP_PAIR`. The values are in 1/20 of a centipawn
+#[rustfmt::skip]
+const IMBALANCE_OURS: [[i32; 6]; 6] = [
+    [   0,    0,    0,    0,    0,    0],
+    [  40,   38,    0,    0,    0,    0],
+    [  32,  255,  -62,    0,    0,    0],
+    [   0,  104,    4,    0,    0,    0],
+    [ -26,   -2,   47,  105, -208,    0],
+    [-189,   24,  117,  133, -134,   -6],
 ];
 
-// macro_rules! if_debug_search {
-//     ($e:expr) => {
-//         if false {
-//             $e
-//         }
-//     };
-// }
-
-pub fn get_piece_value(piece_type: PieceType) -> i16 {
-    debug_assert!(piece_type != PieceType::NONE, "Piece must not be NONE");
-    PIECE_VALUES[piece_type.as_u8() as usize]
-}
+#[rustfmt::skip]
+const IMBALANCE_THEIRS: [[i32; 6]; 6] = [
+    [   0,    0,    0,    0,    0,    0],
+    [  36,    0,    0,    0,    0,    0],
+    [   9,   63,    0,    0,    0,    0],
+    [  59,   65,   42,    0,    0,    0],
+    [  46,   39,   24,  -24,    0,    0],
+    [  97,  100,  -42,  137,  268,    0],
+];
+
+const IMBALANCE_DIVISOR: i32 = 20;
+
+// The endgame score is scaled down by these factors, out of `SCALE_FACTOR_NORMAL`, when the
+// material left makes it hard to convert an advantage
+const SCALE_FACTOR_NORMAL: i32 = 64;
+const SCALE_FACTOR_DRAW: i32 = 0;
+const OPPOSITE_BISHOPS_SCALE: i32 = 24;
+const OPPOSITE_BISHOPS_WITH_PIECES_SCALE: i32 = 48;
+// no pawns and up less than a bishop, against at most a minor piece or against more
+const NO_PAWNS_VS_MINOR_SCALE: i32 = 4;
+const NO_PAWNS_VS_PIECES_SCALE: i32 = 14;
+
+// Contribution of each piece to the game phase, the starting position adds up to
+// `PackedScore::MAX_PHASE`
+const KNIGHT_PHASE: i32 = 1;
+const BISHOP_PHASE: i32 = 1;
+const ROOK_PHASE: i32 = 2;
+const QUEEN_PHASE: i32 = 4;
+
+// Bonus by number of safe squares a piece can move to
+#[rustfmt::skip]
+const KNIGHT_MOBILITY: [PackedScore; 9] = [
+    s(-31, -40), s(-26, -28), s(-6, -15), s(-2, -8), s(1, 2), s(6, 5), s(11, 8), s(14, 10),
+    s(16, 12),
+];
+
+#[rustfmt::skip]
+const BISHOP_MOBILITY: [PackedScore; 14] = [
+    s(-24, -30), s(-10, -12), s(8, -2), s(13, 6), s(19, 12), s(25, 21), s(27, 27), s(31, 28),
+    s(31, 32), s(34, 36), s(40, 39), s(40, 43), s(45, 44), s(49, 48),
+];
+
+#[rustfmt::skip]
+const ROOK_MOBILITY: [PackedScore; 15] = [
+    s(-29, -38), s(-13, -9), s(-7, 14), s(-5, 27), s(-2, 34), s(-1, 41), s(4, 56), s(8, 59),
+    s(15, 66), s(14, 71), s(16, 77), s(19, 82), s(23, 83), s(24, 84), s(29, 85),
+];
+
+#[rustfmt::skip]
+const QUEEN_MOBILITY: [PackedScore; 28] = [
+    s(-19, -18), s(-10, -7), s(1, 4), s(1, 9), s(7, 17), s(11, 27), s(14, 30), s(20, 36),
+    s(21, 39), s(24, 46), s(28, 47), s(30, 52), s(30, 56), s(33, 60), s(33, 61), s(35, 63),
+    s(35, 66), s(36, 68), s(39, 70), s(44, 71), s(44, 74), s(49, 83), s(51, 85), s(51, 87),
+    s(53, 92), s(54, 95), s(56, 103), s(58, 106),
+];
+
+// King danger units added for every square of the king zone attacked by a piece
+const KNIGHT_ATTACK_UNITS: i32 = 2;
+const BISHOP_ATTACK_UNITS: i32 = 2;
+const ROOK_ATTACK_UNITS: i32 = 3;
+const QUEEN_ATTACK_UNITS: i32 = 5;
+
+// Percentage of the attack units that counts, by number of attacking pieces. A lone attacker is
+// rarely dangerous
+const ATTACKER_COUNT_WEIGHT: [i32; 8] = [0, 0, 50, 75, 88, 94, 97, 99];
+
+const MAX_KING_DANGER: i32 = 500;
+
+// Bonus for each pawn right in front of a king on its back rank. Penalty for open and half-open
+// files next to the king, and for enemy pawns advancing toward the king by their rank from our side
+const KING_SHIELD_PAWN: PackedScore = s(10, 0);
+const KING_OPEN_FILE: PackedScore = s(-20, 0);
+const KING_SEMI_OPEN_FILE: PackedScore = s(-12, 0);
+const PAWN_STORM: [PackedScore; 8] =
+    [s(0, 0), s(-10, 0), s(-30, 0), s(-20, 0), s(-10, 0), s(0, 0), s(0, 0), s(0, 0)];
+
+const BISHOP_PAIR: PackedScore = s(30, 50);
+const ROOK_ON_OPEN_FILE: PackedScore = s(24, 12);
+const ROOK_ON_SEMI_OPEN_FILE: PackedScore = s(10, 5);
+const QUEEN_ON_OPEN_FILE: PackedScore = s(5, 5);
+const QUEEN_ON_SEMI_OPEN_FILE: PackedScore = s(2, 2);
+const ROOK_ON_SEVENTH: PackedScore = s(20, 30);
+// Knight or bishop protected by a pawn on a square enemy pawns can never attack
+const KNIGHT_OUTPOST: PackedScore = s(25, 12);
+const BISHOP_OUTPOST: PackedScore = s(12, 6);
+// Per own pawn on the squares of the bishop colour, counted twice when the pawn is blocked
+const BAD_BISHOP_PAWN: PackedScore = s(-2, -4);
+const TRAPPED_BISHOP: PackedScore = s(-100, -100);
+const TRAPPED_ROOK: PackedScore = s(-50, -10);
+
+// Pawn structure terms, by relative rank where it matters
+const PASSED_PAWN_RANK: [PackedScore; 8] =
+    [s(0, 0), s(5, 10), s(5, 15), s(15, 25), s(35, 50), s(60, 90), s(100, 140), s(0, 0)];
+const CANDIDATE_PASSER: [PackedScore; 8] =
+    [s(0, 0), s(2, 5), s(2, 5), s(6, 10), s(12, 20), s(20, 35), s(0, 0), s(0, 0)];
+const CONNECTED_PAWN: [PackedScore; 8] =
+    [s(0, 0), s(3, 0), s(5, 3), s(8, 5), s(15, 12), s(30, 25), s(50, 45), s(0, 0)];
+const DOUBLED_PAWN: PackedScore = s(-10, -25);
+const BACKWARD_PAWN: PackedScore = s(-8, -12);
+
+// Passed pawn terms that depend on the pieces, scaled by how far the pawn has advanced
+const PASSED_BLOCKED: PackedScore = s(-4, -8);
+const PASSED_FREE_PATH: PackedScore = s(6, 12);
+const PASSED_SAFE_STOP: PackedScore = s(3, 6);
+const PASSED_ENEMY_KING_DISTANCE: i16 = 5;
+const PASSED_OWN_KING_DISTANCE: i16 = 2;
+// A passer the enemy king can't catch in a pawn endgame, about the value of a rook
+const UNSTOPPABLE_PASSER: PackedScore = s(0, 500);
+
+// Threats against enemy pieces, counted at half weight for the side that isn't to move since
+// its opponent gets to answer them first
+const HANGING_PIECE: PackedScore = s(35, 18);
+const THREAT_BY_PAWN: PackedScore = s(85, 47);
+const THREAT_BY_PAWN_PUSH: PackedScore = s(24, 20);
+const MINOR_ATTACKS_ROOK: PackedScore = s(38, 28);
+const MINOR_ATTACKS_QUEEN: PackedScore = s(40, 80);
+const ROOK_ATTACKS_QUEEN: PackedScore = s(29, 22);
+const THREAT_BY_KING: PackedScore = s(12, 45);
+
+const DARK_SQUARES: BitBoard = BitBoard::from(0xAA55_AA55_AA55_AA55);
+
+// Material advantage from which the winning side tries to mate the enemy king
+const MOP_UP_MIN_ADVANTAGE: i16 = 200;
+const MOP_UP_CORNER_WEIGHT: i16 = 10;
+const MOP_UP_KING_DISTANCE_WEIGHT: i16 = 4;
 
 struct EvaluationData {
-    material_score: i16,
-    mop_up_score: i16, // score for endgame material
-    piece_square_score: i16,
-    pawn_score: i16,
-    pawn_shield_score: i16,
+    material_score: PackedScore,
+    mop_up_score: PackedScore, // score for endgame material
+    piece_square_score: PackedScore,
+    pawn_score: PackedScore,
+    passed_score: PackedScore, // passed pawn terms that depend on the pieces
+    king_shelter_score: PackedScore,
+    king_attack_score: PackedScore,
+    mobility_score: PackedScore,
+    imbalance_score: PackedScore,
+    pieces_score: PackedScore, // bishop pair, rook files, outposts, trapped pieces
+    threats_score: PackedScore,
 }
 
 impl EvaluationData {
     pub fn new() -> Self {
         EvaluationData {
-            material_score: 0,
-            mop_up_score: 0,
-            piece_square_score: 0,
-            pawn_score: 0,
-            pawn_shield_score: 0,
+            material_score: PackedScore::ZERO,
+            mop_up_score: PackedScore::ZERO,
+            piece_square_score: PackedScore::ZERO,
+            pawn_score: PackedScore::ZERO,
+            passed_score: PackedScore::ZERO,
+            king_shelter_score: PackedScore::ZERO,
+            king_attack_score: PackedScore::ZERO,
+            mobility_score: PackedScore::ZERO,
+            imbalance_score: PackedScore::ZERO,
+            pieces_score: PackedScore::ZERO,
+            threats_score: PackedScore::ZERO,
         }
     }
 
-    pub fn sum(&self) -> i16 {
+    pub fn sum(&self) -> PackedScore {
         self.material_score
+            + self.imbalance_score
             + self.mop_up_score
             + self.piece_square_score
             + self.pawn_score
-            + self.pawn_shield_score
+            + self.passed_score
+            + self.king_shelter_score
+            + self.king_attack_score
+            + self.mobility_score
+            + self.pieces_score
+            + self.threats_score
+    }
+
+    /// Name and value of every term, in the order they are printed by `Evaluation::trace`
+    fn terms(&self) -> [(&'static str, PackedScore); 11] {
+        [
+            ("Material", self.material_score),
+            ("Imbalance", self.imbalance_score),
+            ("Mop up", self.mop_up_score),
+            ("Piece squares", self.piece_square_score),
+            ("Pawns", self.pawn_score),
+            ("Passed pawns", self.passed_score),
+            ("King shelter", self.king_shelter_score),
+            ("King attack", self.king_attack_score),
+            ("Mobility", self.mobility_score),
+            ("Pieces", self.pieces_score),
+            ("Threats", self.threats_score),
+        ]
     }
 }
 
 struct MaterialInfo {
-    pub color: Color,
-    pub material_score: i16,
-    pub _num_pawns: i16,
-    pub _num_knights: i16,
-    pub _num_bishops: i16,
-    pub _num_queens: i16,
-    pub _num_rooks: i16,
-    pub my_pawns: BitBoard,
-    pub enemy_pawns: BitBoard,
-    pub endgame_t: f32, // Transition from midgame to endgame (0->1)
+    pub material_score: PackedScore,
+    pub num_pawns: i16,
+    pub num_knights: i16,
+    pub num_bishops: i16,
+    pub num_queens: i16,
+    pub num_rooks: i16,
+    pub phase: i32, // contribution of the pieces to the game phase
 }
 
 impl MaterialInfo {
     fn new(
-        color: Color,
+        material_score: PackedScore,
         num_pawns: i16,
         num_knights: i16,
         num_bishops: i16,
         num_queens: i16,
         num_rooks: i16,
-        my_pawns: BitBoard,
-        enemy_pawns: BitBoard,
     ) -> Self {
-        let mut material_score = 0;
-        material_score += num_pawns * PAWN_VALUE;
-        material_score += num_knights * KNIGHT_VALUE;
-        material_score += num_bishops * BISHOP_VALUE;
-        material_score += num_rooks * ROOK_VALUE;
-        material_score += num_queens * QUEEN_VALUE;
-
-        // Endgame Transition (0->1)
-        const QUEEN_ENDGAME_WEIGHT: i16 = 45;
-        const ROOK_ENDGAME_WEIGHT: i16 = 20;
-        const BISHOP_ENDGAME_WEIGHT: i16 = 10;
-        const KNIGHT_ENDGAME_WEIGHT: i16 = 10;
-
-        const ENDGAME_START_WEIGHT: i16 = 2 * ROOK_ENDGAME_WEIGHT
-            + 2 * BISHOP_ENDGAME_WEIGHT
-            + 2 * KNIGHT_ENDGAME_WEIGHT
-            + QUEEN_ENDGAME_WEIGHT;
-        let endgame_weight_sum = num_queens * QUEEN_ENDGAME_WEIGHT
-            + num_rooks * ROOK_ENDGAME_WEIGHT
-            + num_bishops * BISHOP_ENDGAME_WEIGHT
-            + num_knights * KNIGHT_ENDGAME_WEIGHT;
-
-        let endgame_t = 1.0 - (endgame_weight_sum as f32 / ENDGAME_START_WEIGHT as f32).min(1.0);
+        let phase = num_knights as i32 * KNIGHT_PHASE
+            + num_bishops as i32 * BISHOP_PHASE
+            + num_rooks as i32 * ROOK_PHASE
+            + num_queens as i32 * QUEEN_PHASE;
 
         MaterialInfo {
-            color,
             material_score,
-            _num_pawns: num_pawns,
-            _num_knights: num_knights,
-            _num_bishops: num_bishops,
-            _num_queens: num_queens,
-            _num_rooks: num_rooks,
-            my_pawns,
-            enemy_pawns,
-            endgame_t,
+            num_pawns,
+            num_knights,
+            num_bishops,
+            num_queens,
+            num_rooks,
+            phase,
         }
     }
-}
 
-// const ENDGAME_MATERIAL_START: i32 = ROOK_VALUE * 2 + BISHOP_VALUE + KNIGHT_VALUE;
+    /// Value of the knights, bishops, rooks and queens
+    fn non_pawn_material(&self) -> i16 {
+        self.material_score.mg() - PAWN_VALUE.mg() * self.num_pawns
+    }
+
+    /// Piece counts in the order of the imbalance tables
+    fn imbalance_counts(&self) -> [i32; 6] {
+        [
+            (self.num_bishops >= 2) as i32,
+            self.num_pawns as i32,
+            self.num_knights as i32,
+            self.num_bishops as i32,
+            self.num_rooks as i32,
+            self.num_queens as i32,
+        ]
+    }
+}
 
 pub struct Evaluation {
     white_score: EvaluationData,
     black_score: EvaluationData,
+    phase: i32,
+    scale_factor: i32,
+    score: i32,                    // final score from white's point of view
+    endgame: Option<&'static str>, // specialised endgame the score comes from
 }
 
 impl Evaluation {
     pub fn new() -> Self {
-        Evaluation { white_score: EvaluationData::new(), black_score: EvaluationData::new() }
+        Evaluation {
+            white_score: EvaluationData::new(),
+            black_score: EvaluationData::new(),
+            phase: PackedScore::MAX_PHASE,
+            scale_factor: SCALE_FACTOR_NORMAL,
+            score: 0,
+            endgame: None,
+        }
     }
 
-    pub fn evaluate_position(&mut self, pos: &Position) -> i16 {
+    pub fn evaluate_position(&mut self, pos: &Position, pawn_table: &mut PawnTable) -> i16 {
+        let perspective = if pos.white_to_move() { 1 } else { -1 };
+
+        // Known endgames have their own evaluation
+        self.endgame = None;
+        if let Some(endgame) = ENDGAMES.probe(pos) {
+            self.endgame = Some(endgame.name);
+            self.score = endgame.evaluate(pos);
+            return self.score as i16 * perspective;
+        }
+
         let white_material = Self::get_material_info(pos, Color::WHITE);
         let black_material = Self::get_material_info(pos, Color::BLACK);
 
+        // Shared by both sides, more pieces than at the start can only come from promotions
+        let phase = (white_material.phase + black_material.phase).min(PackedScore::MAX_PHASE);
+        self.phase = phase;
+
         // Score based on material left on the board
         self.white_score.material_score = white_material.material_score;
         self.black_score.material_score = black_material.material_score;
-
-        // Score based on piece-square tables
-        self.white_score.piece_square_score =
-            self.evaluate_piece_square_table(pos, Color::WHITE, white_material.endgame_t);
-        self.black_score.piece_square_score =
-            self.evaluate_piece_square_table(pos, Color::BLACK, black_material.endgame_t);
-
-        // Evaluate pawns (passed, isolated, sheild)
-        self.white_score.pawn_score = self.evaluate_pawns(&white_material);
-        self.black_score.pawn_score = self.evaluate_pawns(&black_material);
-
-        self.white_score.pawn_shield_score = Self::evaluate_king_pawn_shield(
+        self.white_score.imbalance_score =
+            Self::evaluate_imbalance(&white_material, &black_material);
+        self.black_score.imbalance_score =
+            Self::evaluate_imbalance(&black_material, &white_material);
+
+        // Score based on piece-square tables, summed incrementally by the position
+        self.white_score.piece_square_score = pos.piece_square_score(Color::WHITE);
+        self.black_score.piece_square_score = pos.piece_square_score(Color::BLACK);
+
+        // Evaluate pawns (structure, passers, sheild)
+        let pawns = pawn_table.probe(pos);
+        self.white_score.pawn_score = pawns.score[Color::WHITE.as_usize()];
+        self.black_score.pawn_score = pawns.score[Color::BLACK.as_usize()];
+
+        self.white_score.passed_score =
+            Self::evaluate_passers(pos, Color::WHITE, pawns, &black_material);
+        self.black_score.passed_score =
+            Self::evaluate_passers(pos, Color::BLACK, pawns, &white_material);
+
+        self.white_score.king_shelter_score =
+            pawns.king_shelter(Color::WHITE, pos.get_king_square(Color::WHITE));
+        self.black_score.king_shelter_score =
+            pawns.king_shelter(Color::BLACK, pos.get_king_square(Color::BLACK));
+
+        // Mobility, the pieces attacking the enemy king and the squares attacked by each piece
+        // type are collected at the same time
+        let mut white_attack = KingAttack::default();
+        let mut black_attack = KingAttack::default();
+        let mut white_attacks = Attacks::new(pos, Color::WHITE, pawns);
+        let mut black_attacks = Attacks::new(pos, Color::BLACK, pawns);
+        self.white_score.mobility_score = Self::evaluate_mobility(
+            pos,
             Color::WHITE,
-            pos.get_king_square(Color::WHITE),
-            &white_material,
-            &black_material,
+            pawns,
+            &mut white_attack,
+            &mut white_attacks,
         );
-        self.black_score.pawn_shield_score = Self::evaluate_king_pawn_shield(
+        self.black_score.mobility_score = Self::evaluate_mobility(
+            pos,
             Color::BLACK,
-            pos.get_king_square(Color::BLACK),
-            &black_material,
-            &white_material,
+            pawns,
+            &mut black_attack,
+            &mut black_attacks,
         );
 
-        // Push the king to edge of the board in endgame (for endgame checkmate)
+        self.white_score.king_attack_score =
+            Self::evaluate_king_attack(pos, Color::WHITE, &black_attack, black_material.phase);
+        self.black_score.king_attack_score =
+            Self::evaluate_king_attack(pos, Color::BLACK, &white_attack, white_material.phase);
 
-        let perspective = if pos.white_to_move() { 1 } else { -1 };
-        let score = self.white_score.sum() - self.black_score.sum();
+        self.white_score.pieces_score =
+            Self::evaluate_pieces(pos, Color::WHITE, pawns, &white_material);
+        self.black_score.pieces_score =
+            Self::evaluate_pieces(pos, Color::BLACK, pawns, &black_material);
 
-        // eprintln!(
-        //     "Evaluation: {} (White: {}, Black: {})",
-        //     score,
-        //     self.white_score.sum(),
-        //     self.black_score.sum()
-        // );
+        self.white_score.threats_score =
+            Self::evaluate_threats(pos, Color::WHITE, pawns, &white_attacks);
+        self.black_score.threats_score =
+            Self::evaluate_threats(pos, Color::BLACK, pawns, &black_attacks);
 
-        score * perspective
+        // Push the king to edge of the board in endgame (for endgame checkmate)
+        self.white_score.mop_up_score =
+            Self::evaluate_mop_up(pos, Color::WHITE, &white_material, &black_material);
+        self.black_score.mop_up_score =
+            Self::evaluate_mop_up(pos, Color::BLACK, &black_material, &white_material);
+
+        // Scale the endgame score down when the winning side will struggle to convert
+        let total = self.white_score.sum() - self.black_score.sum();
+        self.scale_factor = if total.eg() >= 0 {
+            Self::scale_factor(pos, Color::WHITE, &white_material, &black_material)
+        } else {
+            Self::scale_factor(pos, Color::BLACK, &black_material, &white_material)
+        };
+        let eg = total.eg() as i32 * self.scale_factor / SCALE_FACTOR_NORMAL;
+
+        self.score = if Self::is_insufficient_material(&white_material, &black_material) {
+            0
+        } else {
+            s(total.mg(), eg as i16).taper(phase)
+        };
+        self.score as i16 * perspective
+    }
+
+    /// Breakdown of the last evaluation by term, from white's point of view
+    pub fn trace(&self) -> String {
+        let mut trace = String::new();
+        if let Some(name) = self.endgame {
+            trace += &format!("Endgame: {}, final evaluation: {} (white side)\n", name, self.score);
+            return trace;
+        }
+
+        trace += "           Term |    White    |    Black    |    Total\n";
+        trace += "                |   MG    EG  |   MG    EG  |   MG    EG\n";
+        let white_terms = self.white_score.terms();
+        let black_terms = self.black_score.terms();
+        let rows =
+            white_terms.iter().zip(black_terms.iter()).map(|(&(name, w), &(_, b))| (name, w, b));
+        let total = ("Total", self.white_score.sum(), self.black_score.sum());
+        for (name, white, black) in rows.chain(std::iter::once(total)) {
+            let diff = white - black;
+            trace += &format!(
+                "{:>15} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5}\n",
+                name,
+                white.mg(),
+                white.eg(),
+                black.mg(),
+                black.eg(),
+                diff.mg(),
+                diff.eg()
+            );
+        }
+        trace += &format!(
+            "Phase: {}/{}, scale factor: {}/{}, final evaluation: {} (white side)\n",
+            self.phase,
+            PackedScore::MAX_PHASE,
+            self.scale_factor,
+            SCALE_FACTOR_NORMAL,
+            self.score
+        );
+        trace
     }
 
     fn get_material_info(pos: &Position, color: Color) -> MaterialInfo {
@@ -188,110 +432,575 @@ impl Evaluation {
         let rook = Piece::get_piece(color, PieceType::ROOK);
         let queen = Piece::get_piece(color, PieceType::QUEEN);
 
-        let my_pawns = pos.bitboards[pawn.as_usize()];
-        let enemy_pawns = Piece::get_piece(color.flip(), PieceType::PAWN);
-        let enemy_pawns = pos.bitboards[enemy_pawns.as_usize()];
-
-        let num_pawns = my_pawns.count() as i16;
+        let num_pawns = pos.bitboards[pawn.as_usize()].count() as i16;
         let num_knights = pos.bitboards[knight.as_usize()].count() as i16;
         let num_bishops = pos.bitboards[bishop.as_usize()].count() as i16;
         let num_rooks = pos.bitboards[rook.as_usize()].count() as i16;
         let num_queens = pos.bitboards[queen.as_usize()].count() as i16;
 
         MaterialInfo::new(
-            color,
+            pos.material(color),
             num_pawns,
             num_knights,
             num_bishops,
             num_queens,
             num_rooks,
-            my_pawns,
-            enemy_pawns,
         )
     }
 
-    fn evaluate_piece_square_table(&self, pos: &Position, color: Color, endgame_t: f32) -> i16 {
-        let mut value = 0i16;
-        value += evaluate_table(pos, &KNIGHT_TABLES, PieceType::KNIGHT, color);
-        value += evaluate_table(pos, &BISHOP_TABLES, PieceType::BISHOP, color);
-        value += evaluate_table(pos, &ROOK_TABLES, PieceType::ROOK, color);
-        value += evaluate_table(pos, &QUEEN_TABLES, PieceType::QUEEN, color);
+    /// Interactions between the pieces of both sides, like the knight getting stronger with
+    /// more pawns on the board or the queen against several minor pieces
+    fn evaluate_imbalance(material: &MaterialInfo, enemy_material: &MaterialInfo) -> PackedScore {
+        let ours = material.imbalance_counts();
+        let theirs = enemy_material.imbalance_counts();
+
+        let mut bonus = 0;
+        for (i, &count) in ours.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let value: i32 = (0..=i)
+                .map(|j| IMBALANCE_OURS[i][j] * ours[j] + IMBALANCE_THEIRS[i][j] * theirs[j])
+                .sum();
+            bonus += count * value;
+        }
+
+        let bonus = (bonus / IMBALANCE_DIVISOR) as i16;
+        s(bonus, bonus)
+    }
+
+    /// Neither side has enough material to force mate
+    fn is_insufficient_material(white: &MaterialInfo, black: &MaterialInfo) -> bool {
+        let only_minors =
+            |m: &MaterialInfo| m.num_pawns == 0 && m.num_rooks == 0 && m.num_queens == 0;
+        let minors = |m: &MaterialInfo| m.num_knights + m.num_bishops;
+        let two_knights = |m: &MaterialInfo| m.num_knights == 2 && m.num_bishops == 0;
+
+        only_minors(white)
+            && only_minors(black)
+            && ((minors(white) <= 1 && minors(black) <= 1)
+                || (two_knights(white) && minors(black) == 0)
+                || (two_knights(black) && minors(white) == 0))
+    }
+
+    /// Factor by which the endgame score of the `strong` side is scaled, for material that is
+    /// much harder to win with than its value suggests
+    fn scale_factor(
+        pos: &Position,
+        strong: Color,
+        material: &MaterialInfo,
+        enemy_material: &MaterialInfo,
+    ) -> i32 {
+        let npm = material.non_pawn_material();
+        let enemy_npm = enemy_material.non_pawn_material();
+
+        // without pawns, being up less than a bishop is rarely enough (KRKB, KBNKN, ...)
+        if material.num_pawns == 0 && npm - enemy_npm <= BISHOP_VALUE.mg() {
+            return if npm < ROOK_VALUE.mg() {
+                SCALE_FACTOR_DRAW
+            } else if enemy_npm <= BISHOP_VALUE.mg() {
+                NO_PAWNS_VS_MINOR_SCALE
+            } else {
+                NO_PAWNS_VS_PIECES_SCALE
+            };
+        }
+
+        let bishops = |color| pos.bitboards[Piece::get_piece(color, PieceType::BISHOP).as_usize()];
+
+        // a bishop that doesn't control the promotion square of its rook pawns can't drive the
+        // enemy king out of the corner
+        if npm == BISHOP_VALUE.mg() && material.num_bishops == 1 {
+            let pawns = pos.bitboards[Piece::get_piece(strong, PieceType::PAWN).as_usize()];
+            for file in [File::A, File::H] {
+                if (pawns & !BitBoard::from(FILE_MASKS[file.0 as usize])).any() {
+                    continue;
+                }
+                let promotion_rank = if strong == Color::WHITE { Rank::_8 } else { Rank::_1 };
+                let promotion = Square::make(file, promotion_rank);
+                let bishop_sq = bishops(strong).iter().next().unwrap();
+                let enemy_king_sq = pos.get_king_square(strong.flip());
+                if DARK_SQUARES.test_sq(bishop_sq) != DARK_SQUARES.test_sq(promotion)
+                    && distance(enemy_king_sq, promotion) <= 1
+                {
+                    return SCALE_FACTOR_DRAW;
+                }
+            }
+        }
 
-        let pawn_early = evaluate_table(pos, &PAWN_START_TABLES, PieceType::PAWN, color);
-        let pawn_late = evaluate_table(pos, &PAWN_END_TABLES, PieceType::PAWN, color);
-        value += (pawn_early as f32 * (1.0 - endgame_t)) as i16;
-        value += (pawn_late as f32 * endgame_t) as i16;
+        if material.num_bishops == 1 && enemy_material.num_bishops == 1 {
+            let bishop_sq = bishops(strong).iter().next().unwrap();
+            let enemy_bishop_sq = bishops(strong.flip()).iter().next().unwrap();
+            if DARK_SQUARES.test_sq(bishop_sq) != DARK_SQUARES.test_sq(enemy_bishop_sq) {
+                return if npm == BISHOP_VALUE.mg() && enemy_npm == BISHOP_VALUE.mg() {
+                    OPPOSITE_BISHOPS_SCALE
+                } else {
+                    OPPOSITE_BISHOPS_WITH_PIECES_SCALE
+                };
+            }
+        }
 
-        let king_early = evaluate_table(pos, &KING_START_TABLES, PieceType::KING, color);
-        let king_late = evaluate_table(pos, &KING_END_TABLES, PieceType::KING, color);
-        value += (king_early as f32 * (1.0 - endgame_t)) as i16;
-        value += (king_late as f32 * endgame_t) as i16;
+        SCALE_FACTOR_NORMAL
+    }
 
-        value
+    /// Bonus for the squares reachable by each piece, squares occupied by own pieces or attacked
+    /// by enemy pawns are not counted. The pieces attacking the enemy king zone are added to
+    /// `king_attack` and the attacked squares to `piece_attacks`
+    fn evaluate_mobility(
+        pos: &Position,
+        color: Color,
+        pawns: &PawnEntry,
+        king_attack: &mut KingAttack,
+        piece_attacks: &mut Attacks,
+    ) -> PackedScore {
+        let occupancy = pos.state.occupancies[Color::BOTH.as_usize()];
+        let area =
+            !(pos.state.occupancies[color.as_usize()] | pawns.attacks[color.flip().as_usize()]);
+        let king_zone = king_zone(pos.get_king_square(color.flip()));
+        let pieces = |piece_type| pos.bitboards[Piece::get_piece(color, piece_type).as_usize()];
+
+        let mut score = PackedScore::ZERO;
+        for sq in pieces(PieceType::KNIGHT).iter() {
+            let attacks = KNIGHT_MASKS[sq.as_usize()];
+            score += KNIGHT_MOBILITY[(attacks & area).count() as usize];
+            king_attack.add(attacks & king_zone, KNIGHT_ATTACK_UNITS);
+            piece_attacks.add(PieceType::KNIGHT, attacks);
+        }
+        for sq in pieces(PieceType::BISHOP).iter() {
+            let attacks = get_bishop_attack_mask(occupancy, sq);
+            score += BISHOP_MOBILITY[(attacks & area).count() as usize];
+            king_attack.add(attacks & king_zone, BISHOP_ATTACK_UNITS);
+            piece_attacks.add(PieceType::BISHOP, attacks);
+        }
+        for sq in pieces(PieceType::ROOK).iter() {
+            let attacks = get_rook_attack_mask(occupancy, sq);
+            score += ROOK_MOBILITY[(attacks & area).count() as usize];
+            king_attack.add(attacks & king_zone, ROOK_ATTACK_UNITS);
+            piece_attacks.add(PieceType::ROOK, attacks);
+        }
+        for sq in pieces(PieceType::QUEEN).iter() {
+            let attacks =
+                get_bishop_attack_mask(occupancy, sq) | get_rook_attack_mask(occupancy, sq);
+            score += QUEEN_MOBILITY[(attacks & area).count() as usize];
+            king_attack.add(attacks & king_zone, QUEEN_ATTACK_UNITS);
+            piece_attacks.add(PieceType::QUEEN, attacks);
+        }
+        score
     }
 
-    fn evaluate_pawns(&self, material: &MaterialInfo) -> i16 {
-        let mut score = 0;
-        score += Self::evaluate_passed_pawns(material);
-        score += Self::evaluate_isolated_pawns(material);
+    /// Penalty for the pieces attacking the king zone. The danger grows with the square of the
+    /// attack units and fades as the attacker trades its pieces (`enemy_phase`)
+    fn evaluate_king_attack(
+        pos: &Position,
+        color: Color,
+        attack: &KingAttack,
+        enemy_phase: i32,
+    ) -> PackedScore {
+        let king_zone = king_zone(pos.get_king_square(color));
+        // squares of the zone covered by any enemy piece, pawns and king included
+        let attacked = pos.state.attack_mask[color.flip().as_usize()] & king_zone;
+
+        let units = attack.units + attacked.count() as i32;
+        let danger = units * ATTACKER_COUNT_WEIGHT[attack.attackers.min(7)] / 100;
+        let danger = (danger * danger / 4).min(MAX_KING_DANGER);
+        let danger =
+            danger * enemy_phase.min(PackedScore::MAX_PHASE / 2) / (PackedScore::MAX_PHASE / 2);
+
+        PackedScore::new(-danger as i16, (-danger / 4) as i16)
+    }
+
+    /// Pawn terms that only depend on the pawn structure, cached in the pawn table
+    pub(super) fn evaluate_pawn_structure(pawns: &PawnEntry, color: Color) -> PackedScore {
+        let c = color.as_usize();
+        let mut score = PackedScore::ZERO;
+        score += Self::evaluate_passed_pawns(pawns, color);
+        score += Self::evaluate_isolated_pawns(pawns, color);
+        score += DOUBLED_PAWN * pawns.doubled_count[c] as i32;
+        score += BACKWARD_PAWN * pawns.backward_count[c] as i32;
+        for sq in pawns.connected[c].iter() {
+            score += CONNECTED_PAWN[relative_rank(color, sq) as usize];
+        }
+        for sq in pawns.candidates[c].iter() {
+            score += CANDIDATE_PASSER[relative_rank(color, sq) as usize];
+        }
         score
     }
 
-    fn evaluate_passed_pawns(material: &MaterialInfo) -> i16 {
-        const PASSED_PAWN_BONUSES: [i16; 7] = [0, 120, 80, 50, 30, 15, 15];
+    fn evaluate_passed_pawns(pawns: &PawnEntry, color: Color) -> PackedScore {
+        let mut score = PackedScore::ZERO;
 
-        let mut score = 0;
+        for sq in pawns.passed[color.as_usize()].iter() {
+            debug_assert!(relative_rank(color, sq) < 7);
+            score += PASSED_PAWN_RANK[relative_rank(color, sq) as usize];
+        }
 
-        for sq in material.my_pawns.iter() {
-            let mask = PASSED_PAWN_MASKS[material.color.as_usize()][sq.as_u8() as usize];
-            if (material.enemy_pawns & mask).none() {
-                let (_, rank) = sq.file_rank();
-                debug_assert!(rank.0 < 7);
-                let idx = if material.color == Color::WHITE { rank.0 } else { 7 - rank.0 };
-                score += PASSED_PAWN_BONUSES[idx as usize];
+        score
+    }
+
+    /// Passed pawn terms that depend on the pieces: whether the way to promotion is blocked or
+    /// controlled by the enemy, the distance of both kings to the pawn in the endgame and the
+    /// rule of the square once the enemy has no pieces left
+    fn evaluate_passers(
+        pos: &Position,
+        color: Color,
+        pawns: &PawnEntry,
+        enemy_material: &MaterialInfo,
+    ) -> PackedScore {
+        let enemy = color.flip();
+        let occupancy = pos.state.occupancies[Color::BOTH.as_usize()];
+        let enemy_attacks = pos.state.attack_mask[enemy.as_usize()];
+        let king_sq = pos.get_king_square(color);
+        let enemy_king_sq = pos.get_king_square(enemy);
+
+        let mut score = PackedScore::ZERO;
+        let mut unstoppable = false;
+        for sq in pawns.passed[color.as_usize()].iter() {
+            let rank = relative_rank(color, sq);
+            let (file, _) = sq.file_rank();
+            let path = PASSED_PAWN_MASKS[color.as_usize()][sq.as_usize()]
+                & BitBoard::from(FILE_MASKS[file.0 as usize]);
+            let stop = stop_square(color, sq);
+
+            // pawns far from promotion are not worth helping yet
+            let weight = rank.saturating_sub(2) as i32;
+            if weight > 0 {
+                if occupancy.test_sq(stop) {
+                    score += PASSED_BLOCKED * weight;
+                } else if (path & (occupancy | enemy_attacks)).none() {
+                    score += PASSED_FREE_PATH * weight;
+                } else if !enemy_attacks.test_sq(stop) {
+                    score += PASSED_SAFE_STOP * weight;
+                }
+
+                let proximity = distance(enemy_king_sq, stop).min(5) * PASSED_ENEMY_KING_DISTANCE
+                    - distance(king_sq, stop).min(5) * PASSED_OWN_KING_DISTANCE;
+                score += s(0, proximity * weight as i16);
+            }
+
+            // the enemy king must be inside the square of the pawn to catch it
+            if enemy_material.phase == 0 && (path & occupancy).none() {
+                let promotion = path.iter().find(|&sq| relative_rank(color, sq) == 7).unwrap();
+                let moves_to_promote = (7 - rank).min(5) as i16;
+                let tempo = if pos.side_to_move == enemy { 1 } else { 0 };
+                if distance(enemy_king_sq, promotion) - tempo > moves_to_promote {
+                    unstoppable = true;
+                }
             }
         }
 
+        if unstoppable {
+            score += UNSTOPPABLE_PASSER;
+        }
         score
     }
 
-    fn evaluate_king_pawn_shield(
+    /// Positional terms of the pieces, beyond their square and mobility
+    fn evaluate_pieces(
+        pos: &Position,
         color: Color,
-        king_sq: Square,
+        pawns: &PawnEntry,
         material: &MaterialInfo,
-        enemy_material: &MaterialInfo,
-    ) -> i16 {
-        // @TODO: use a better score system
-        // const KING_PAWN_SHIELD_SCORES: [i32; 6] = [4, 7, 4, 3, 6, 3];
-        if enemy_material.endgame_t >= 1.0 {
-            // In endgame, king pawn shield is not important
-            return 0;
+    ) -> PackedScore {
+        let c = color.as_usize();
+        let enemy = color.flip();
+        let occupancy = pos.state.occupancies[Color::BOTH.as_usize()];
+        let pieces = |piece_type| pos.bitboards[Piece::get_piece(color, piece_type).as_usize()];
+        let my_pawns = pawns.pawns[c];
+        let enemy_pawns = pawns.pawns[enemy.as_usize()];
+        let is_outpost = |sq: Square| {
+            (3..=5).contains(&relative_rank(color, sq))
+                && pawns.attacks[c].test_sq(sq)
+                && !pawns.attack_spans[enemy.as_usize()].test_sq(sq)
+        };
+
+        let mut score = PackedScore::ZERO;
+        if material.num_bishops >= 2 {
+            score += BISHOP_PAIR;
         }
 
-        let mask = KING_PAWN_SHIELD_MASKS[color.as_usize()][king_sq.as_usize()];
-        let count = material.my_pawns & mask;
+        for sq in pieces(PieceType::KNIGHT).iter() {
+            if is_outpost(sq) {
+                score += KNIGHT_OUTPOST;
+            }
+        }
 
-        // let missing = mask.count() as i16 - count.count() as i16;
+        // pawns that can't move, whether blocked by a pawn or by a piece
+        let blocked_pawns = if color == Color::WHITE {
+            BitBoard::from(my_pawns.get() & (occupancy.get() >> 8))
+        } else {
+            BitBoard::from(my_pawns.get() & (occupancy.get() << 8))
+        };
+        for sq in pieces(PieceType::BISHOP).iter() {
+            if is_outpost(sq) {
+                score += BISHOP_OUTPOST;
+            }
 
-        const MISSING_PAWN_PENALTIES: i16 = 10;
-        count.count() as i16 * MISSING_PAWN_PENALTIES
-    }
+            let same_colour = if DARK_SQUARES.test_sq(sq) { DARK_SQUARES } else { !DARK_SQUARES };
+            let bad_pawns =
+                (my_pawns & same_colour).count() + (blocked_pawns & same_colour).count();
+            score += BAD_BISHOP_PAWN * bad_pawns as i32;
+
+            // a bishop taking the a7 or h7 pawn gets locked in by b6 or g6
+            let trapped = match (color, sq) {
+                (Color::WHITE, Square::A7) => enemy_pawns.test_sq(Square::B6),
+                (Color::WHITE, Square::H7) => enemy_pawns.test_sq(Square::G6),
+                (Color::BLACK, Square::A2) => enemy_pawns.test_sq(Square::B3),
+                (Color::BLACK, Square::H2) => enemy_pawns.test_sq(Square::G3),
+                _ => false,
+            };
+            if trapped {
+                score += TRAPPED_BISHOP;
+            }
+        }
 
-    fn evaluate_isolated_pawns(material: &MaterialInfo) -> i16 {
-        const ISOLATED_PAWN_PENALTY_BY_COUNT: [i16; 9] =
-            [0, -10, -25, -50, -75, -75, -75, -75, -75];
+        let enemy_king_sq = pos.get_king_square(enemy);
+        let king_sq = pos.get_king_square(color);
+        let castling_rights =
+            if color == Color::WHITE { CastlingRight::KQ } else { CastlingRight::kq };
+        for sq in pieces(PieceType::ROOK).iter() {
+            let (file, _) = sq.file_rank();
+            let file_bit = 1u8 << file.0;
+            if pawns.open_files & file_bit != 0 {
+                score += ROOK_ON_OPEN_FILE;
+            } else if pawns.semi_open_files[c] & file_bit != 0 {
+                score += ROOK_ON_SEMI_OPEN_FILE;
+            }
 
-        let mut isolated_count = 0;
-        for sq in material.my_pawns.iter() {
+            // the seventh rank matters when it holds enemy pawns or cuts off the enemy king
+            let seventh_rank =
+                BitBoard::from(RANK_MASKS[if color == Color::WHITE { 6 } else { 1 }]);
+            if relative_rank(color, sq) == 6
+                && ((enemy_pawns & seventh_rank).any() || relative_rank(color, enemy_king_sq) == 7)
+            {
+                score += ROOK_ON_SEVENTH;
+            }
+
+            // a rook boxed in the corner by a king that moved without castling
+            let (king_file, _) = king_sq.file_rank();
+            let same_side = (king_file.0 < 4) == (file.0 < king_file.0);
+            if relative_rank(color, sq) <= 1
+                && relative_rank(color, king_sq) == 0
+                && same_side
+                && file.0 != king_file.0
+                && pos.state.castling_rights & castling_rights == 0
+                && (get_rook_attack_mask(occupancy, sq) & !pos.state.occupancies[c]).count() <= 3
+            {
+                score += TRAPPED_ROOK;
+            }
+        }
+
+        for sq in pieces(PieceType::QUEEN).iter() {
             let (file, _) = sq.file_rank();
-            let mask = ISOLATED_PAWN_MASKS[file.0 as usize];
-            if (material.my_pawns & mask).none() {
-                isolated_count += 1;
+            let file_bit = 1u8 << file.0;
+            if pawns.open_files & file_bit != 0 {
+                score += QUEEN_ON_OPEN_FILE;
+            } else if pawns.semi_open_files[c] & file_bit != 0 {
+                score += QUEEN_ON_SEMI_OPEN_FILE;
             }
         }
 
-        ISOLATED_PAWN_PENALTY_BY_COUNT[isolated_count as usize]
+        score
     }
+
+    /// Bonus for attacks on enemy pieces: undefended pieces, pieces attacked by a less valuable
+    /// piece, pawn pushes that would attack a piece and undefended pieces next to our king
+    fn evaluate_threats(
+        pos: &Position,
+        color: Color,
+        pawns: &PawnEntry,
+        attacks: &Attacks,
+    ) -> PackedScore {
+        let c = color.as_usize();
+        let enemy = color.flip();
+        let e = enemy.as_usize();
+        let enemy_pieces =
+            |piece_type| pos.bitboards[Piece::get_piece(enemy, piece_type).as_usize()];
+        let our_attacks = pos.state.attack_mask[c];
+        let enemy_attacks = pos.state.attack_mask[e];
+        let enemy_king = enemy_pieces(PieceType::KING);
+        let non_pawn_enemies =
+            pos.state.occupancies[e] & !enemy_pieces(PieceType::PAWN) & !enemy_king;
+
+        let mut score = PackedScore::ZERO;
+
+        let hanging = non_pawn_enemies & our_attacks & !enemy_attacks;
+        score += HANGING_PIECE * hanging.count() as i32;
+
+        let by_pawn = attacks.of(PieceType::PAWN) & non_pawn_enemies;
+        score += THREAT_BY_PAWN * by_pawn.count() as i32;
+
+        let minors = attacks.of(PieceType::KNIGHT) | attacks.of(PieceType::BISHOP);
+        score += MINOR_ATTACKS_ROOK * (minors & enemy_pieces(PieceType::ROOK)).count() as i32;
+        score += MINOR_ATTACKS_QUEEN * (minors & enemy_pieces(PieceType::QUEEN)).count() as i32;
+        let by_rook = attacks.of(PieceType::ROOK) & enemy_pieces(PieceType::QUEEN);
+        score += ROOK_ATTACKS_QUEEN * by_rook.count() as i32;
+
+        let weak = pos.state.occupancies[e] & !enemy_king & !enemy_attacks;
+        if (attacks.of(PieceType::KING) & weak).any() {
+            score += THREAT_BY_KING;
+        }
+
+        // squares our pawns can safely push to, from where they would attack a piece
+        let empty = !pos.state.occupancies[Color::BOTH.as_usize()].get();
+        let my_pawns = pawns.pawns[c].get();
+        let pushes = if color == Color::WHITE {
+            let single = (my_pawns << 8) & empty;
+            single | (((single & BitBoard::MASK_3) << 8) & empty)
+        } else {
+            let single = (my_pawns >> 8) & empty;
+            single | (((single & BitBoard::MASK_6) >> 8) & empty)
+        };
+        let safe = !pawns.attacks[e] & (!enemy_attacks | our_attacks);
+        let pushes = BitBoard::from(pushes) & safe;
+        let by_push = pawn_attacks(pushes, color) & non_pawn_enemies;
+        score += THREAT_BY_PAWN_PUSH * by_push.count() as i32;
+
+        if pos.side_to_move != color { s(score.mg() / 2, score.eg() / 2) } else { score }
+    }
+
+    /// Endgame bonus for the side that is materially winning, for driving the enemy king to the
+    /// edge of the board and walking its own king toward it, which most mates need
+    fn evaluate_mop_up(
+        pos: &Position,
+        color: Color,
+        material: &MaterialInfo,
+        enemy_material: &MaterialInfo,
+    ) -> PackedScore {
+        let advantage = material.material_score.eg() - enemy_material.material_score.eg();
+        // without pieces there is nothing to mate with, pawns have to promote first
+        if advantage < MOP_UP_MIN_ADVANTAGE || material.phase == 0 {
+            return PackedScore::ZERO;
+        }
+
+        let king_sq = pos.get_king_square(color);
+        let enemy_king_sq = pos.get_king_square(color.flip());
+        let corner = center_distance(enemy_king_sq) * MOP_UP_CORNER_WEIGHT;
+        let closeness =
+            (14 - manhattan_distance(king_sq, enemy_king_sq)) * MOP_UP_KING_DISTANCE_WEIGHT;
+        s(0, corner + closeness)
+    }
+
+    /// Shield, open files and enemy pawn storms around the king, cached in the pawn table
+    pub(super) fn evaluate_king_shelter(
+        pawns: &PawnEntry,
+        color: Color,
+        king_sq: Square,
+    ) -> PackedScore {
+        let c = color.as_usize();
+        let shield = pawns.pawns[c] & KING_PAWN_SHIELD_MASKS[c][king_sq.as_usize()];
+        let mut score = KING_SHIELD_PAWN * shield.count() as i32;
+
+        let (king_file, _) = king_sq.file_rank();
+        for f in king_file.0.saturating_sub(1)..=(king_file.0 + 1).min(7) {
+            let file_bit = 1u8 << f;
+            if pawns.open_files & file_bit != 0 {
+                score += KING_OPEN_FILE;
+            } else if pawns.semi_open_files[c] & file_bit != 0 {
+                score += KING_SEMI_OPEN_FILE;
+            }
+
+            // the enemy pawn closest to our side of the board
+            let storm =
+                pawns.pawns[color.flip().as_usize()] & BitBoard::from(FILE_MASKS[f as usize]);
+            let front_pawn =
+                if color == Color::WHITE { storm.iter().next() } else { storm.iter().last() };
+            if let Some(sq) = front_pawn {
+                let (_, rank) = sq.file_rank();
+                let relative_rank = if color == Color::WHITE { rank.0 } else { 7 - rank.0 };
+                score += PAWN_STORM[relative_rank as usize];
+            }
+        }
+
+        score
+    }
+
+    fn evaluate_isolated_pawns(pawns: &PawnEntry, color: Color) -> PackedScore {
+        const ISOLATED_PAWN_PENALTY_BY_COUNT: [PackedScore; 9] = [
+            s(0, 0),
+            s(-10, -10),
+            s(-25, -25),
+            s(-50, -50),
+            s(-75, -75),
+            s(-75, -75),
+            s(-75, -75),
+            s(-75, -75),
+            s(-75, -75),
+        ];
+
+        ISOLATED_PAWN_PENALTY_BY_COUNT[pawns.isolated_count[color.as_usize()] as usize]
+    }
+}
+
+/// Pieces attacking the king zone of one side
+#[derive(Default)]
+struct KingAttack {
+    attackers: usize,
+    units: i32,
+}
+
+impl KingAttack {
+    fn add(&mut self, zone_attacks: BitBoard, units: i32) {
+        if zone_attacks.any() {
+            self.attackers += 1;
+            self.units += units * zone_attacks.count() as i32;
+        }
+    }
+}
+
+/// Squares attacked by each piece type of one side
+struct Attacks {
+    by_type: [BitBoard; PieceType::COUNT as usize],
+}
+
+impl Attacks {
+    /// Starts with the pawn and king attacks, the pieces are added by the mobility evaluation
+    fn new(pos: &Position, color: Color, pawns: &PawnEntry) -> Self {
+        let mut by_type = [BitBoard::new(); PieceType::COUNT as usize];
+        by_type[PieceType::PAWN.as_usize()] = pawns.attacks[color.as_usize()];
+        by_type[PieceType::KING.as_usize()] = KING_MASKS[pos.get_king_square(color).as_usize()];
+        Self { by_type }
+    }
+
+    fn add(&mut self, piece_type: PieceType, attacks: BitBoard) {
+        self.by_type[piece_type.as_usize()] |= attacks;
+    }
+
+    fn of(&self, piece_type: PieceType) -> BitBoard {
+        self.by_type[piece_type.as_usize()]
+    }
+}
+
+/// Rank of a square from the point of view of `color`, 0 is its back rank
+fn relative_rank(color: Color, sq: Square) -> u8 {
+    let (_, rank) = sq.file_rank();
+    if color == Color::WHITE { rank.0 } else { 7 - rank.0 }
+}
+
+/// Number of king moves between two squares (0 to 7)
+pub(super) fn distance(a: Square, b: Square) -> i16 {
+    let (a_file, a_rank) = a.file_rank();
+    let (b_file, b_rank) = b.file_rank();
+    (a_file.0 as i16 - b_file.0 as i16).abs().max((a_rank.0 as i16 - b_rank.0 as i16).abs())
+}
+
+/// Number of king steps along files and ranks between two squares (0 to 14)
+fn manhattan_distance(a: Square, b: Square) -> i16 {
+    let (a_file, a_rank) = a.file_rank();
+    let (b_file, b_rank) = b.file_rank();
+    (a_file.0 as i16 - b_file.0 as i16).abs() + (a_rank.0 as i16 - b_rank.0 as i16).abs()
+}
+
+/// Manhattan distance to the closest of the four center squares (0 to 6)
+pub(super) fn center_distance(sq: Square) -> i16 {
+    let (file, rank) = sq.file_rank();
+    let (file, rank) = (file.0 as i16, rank.0 as i16);
+    (3 - file).max(file - 4) + (3 - rank).max(rank - 4)
+}
+
+/// The king square and the squares around it
+fn king_zone(king_sq: Square) -> BitBoard {
+    let mut zone = KING_MASKS[king_sq.as_usize()];
+    zone.set_sq(king_sq);
+    zone
 }
 
 const FILE_MASKS: [u64; 8] = [
@@ -361,7 +1070,7 @@ const fn passed_pawn_mask<const IS_WHITE: bool>(square: Square) -> BitBoard {
     BitBoard::from(!mask)
 }
 
-const ISOLATED_PAWN_MASKS: [BitBoard; 8] = [
+pub(super) const ISOLATED_PAWN_MASKS: [BitBoard; 8] = [
     adjacent_files_mask(File::A),
     adjacent_files_mask(File::B),
     adjacent_files_mask(File::C),
@@ -382,9 +1091,9 @@ const fn passed_pawn_mask_all<const IS_WHITE: bool>() -> [BitBoard; 64] {
     masks
 }
 
-const PASSED_PAWN_MASKS: [[BitBoard; 64]; 2] = [
+pub(super) const PASSED_PAWN_MASKS: [[BitBoard; 64]; 2] = [
     passed_pawn_mask_all::<true>(),  // White
-    passed_pawn_mask_all::<false>(), // White
+    passed_pawn_mask_all::<false>(), // Black
 ];
 
 const fn king_pawn_sheild_mask<const IS_WHITE: bool>(sq: u8) -> BitBoard {
@@ -461,29 +1170,308 @@ mod tests {
     fn test_passed_pawns() {
         let fen = "rnbqkbnr/3pp2p/8/6P1/6p1/1P3p2/P1PP4/RNBQKBNR w KQkq - 0 1";
         let pos = Position::from_fen(fen).unwrap();
-        let white_material = Evaluation::get_material_info(&pos, Color::WHITE);
-        let white_passed_pawns_score = Evaluation::evaluate_passed_pawns(&white_material);
-        assert_eq!(white_passed_pawns_score, 200);
+        let pawns = PawnEntry::new(&pos);
+        let white_passed_pawns_score = Evaluation::evaluate_passed_pawns(&pawns, Color::WHITE);
+        assert_eq!(white_passed_pawns_score, PASSED_PAWN_RANK[1] + PASSED_PAWN_RANK[2]);
+
+        let black_passed_pawns_score = Evaluation::evaluate_passed_pawns(&pawns, Color::BLACK);
+        assert_eq!(black_passed_pawns_score, PASSED_PAWN_RANK[5] + PASSED_PAWN_RANK[4]);
+        assert!(PASSED_PAWN_RANK[6].eg() > PASSED_PAWN_RANK[1].eg());
+    }
 
-        let black_material = Evaluation::get_material_info(&pos, Color::BLACK);
-        let black_passed_pawns_score = Evaluation::evaluate_passed_pawns(&black_material);
-        assert_eq!(black_passed_pawns_score, 45);
+    #[test]
+    fn test_pawn_structure() {
+        let structure = |fen: &str| {
+            let pos = Position::from_fen(fen).unwrap();
+            Evaluation::evaluate_pawn_structure(&PawnEntry::new(&pos), Color::WHITE)
+        };
+
+        // the rear pawn of a doubled pair is not passed
+        let doubled = structure("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1");
+        let two_isolated = s(-25, -25);
+        assert_eq!(doubled, PASSED_PAWN_RANK[2] + two_isolated + DOUBLED_PAWN);
+
+        // d3 can't be supported and e5 controls d4
+        let backward = structure("4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1");
+        assert_eq!(backward, PASSED_PAWN_RANK[3] + CONNECTED_PAWN[3] * 2 + BACKWARD_PAWN);
+
+        let phalanx = structure("4k3/8/8/3PP3/8/8/8/4K3 w - - 0 1");
+        assert_eq!(phalanx, (PASSED_PAWN_RANK[4] + CONNECTED_PAWN[4]) * 2);
+
+        // b4 is supported by a3 and c3 against the single a7 sentry
+        let candidate = structure("4k3/p7/8/8/1P6/P1P5/8/4K3 w - - 0 1");
+        assert_eq!(candidate, PASSED_PAWN_RANK[2] + CONNECTED_PAWN[3] + CANDIDATE_PASSER[3]);
+    }
+
+    #[test]
+    fn test_passers() {
+        let passers = |fen: &str| {
+            let pos = Position::from_fen(fen).unwrap();
+            let enemy_material = Evaluation::get_material_info(&pos, Color::BLACK);
+            Evaluation::evaluate_passers(&pos, Color::WHITE, &PawnEntry::new(&pos), &enemy_material)
+        };
+
+        // the kings are 4 and 5 moves away from e6
+        let proximity = s(0, (4 * PASSED_ENEMY_KING_DISTANCE - 5 * PASSED_OWN_KING_DISTANCE) * 2);
+        let blocked = passers("k7/8/4n3/4P3/8/8/8/4K3 w - - 0 1");
+        let free = passers("k7/8/8/4P3/8/8/8/n3K3 w - - 0 1");
+        assert_eq!(blocked, PASSED_BLOCKED * 2 + proximity);
+        assert_eq!(free, PASSED_FREE_PATH * 2 + proximity);
+
+        // the black king is outside the square of the a5 pawn, unless it moves first
+        let outside = passers("8/8/4k3/P7/8/8/8/K7 w - - 0 1");
+        let inside = passers("8/8/4k3/P7/8/8/8/K7 b - - 0 1");
+        assert_eq!(outside - inside, UNSTOPPABLE_PASSER);
     }
 
     #[test]
     fn test_isolated_pawns() {
-        let pos = Position::new();
-        let white_material = Evaluation::get_material_info(&pos, Color::WHITE);
-        let black_material = Evaluation::get_material_info(&pos, Color::BLACK);
-        assert_eq!(Evaluation::evaluate_isolated_pawns(&white_material), 0);
-        assert_eq!(Evaluation::evaluate_isolated_pawns(&black_material), 0);
+        let pawns = PawnEntry::new(&Position::new());
+        assert_eq!(Evaluation::evaluate_isolated_pawns(&pawns, Color::WHITE), s(0, 0));
+        assert_eq!(Evaluation::evaluate_isolated_pawns(&pawns, Color::BLACK), s(0, 0));
 
         let pos = Position::from_fen("rnbqkbnr/pp3ppp/8/1P1p2P1/6P1/8/3PP3/RNBQKBNR b KQkq - 0 1")
             .unwrap();
-        let white_material = Evaluation::get_material_info(&pos, Color::WHITE);
-        let black_material = Evaluation::get_material_info(&pos, Color::BLACK);
-        assert_eq!(Evaluation::evaluate_isolated_pawns(&white_material), -50);
-        assert_eq!(Evaluation::evaluate_isolated_pawns(&black_material), -10);
+        let pawns = PawnEntry::new(&pos);
+        assert_eq!(Evaluation::evaluate_isolated_pawns(&pawns, Color::WHITE), s(-50, -50));
+        assert_eq!(Evaluation::evaluate_isolated_pawns(&pawns, Color::BLACK), s(-10, -10));
+    }
+
+    fn mobility_score(
+        pos: &Position,
+        color: Color,
+        pawns: &PawnEntry,
+        king_attack: &mut KingAttack,
+    ) -> PackedScore {
+        let mut attacks = Attacks::new(pos, color, pawns);
+        Evaluation::evaluate_mobility(pos, color, pawns, king_attack, &mut attacks)
+    }
+
+    #[test]
+    fn test_mobility() {
+        let pos = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        let pawns = PawnEntry::new(&pos);
+        let mobility = mobility_score(&pos, Color::WHITE, &pawns, &mut KingAttack::default());
+        assert_eq!(mobility, ROOK_MOBILITY[10]);
+
+        // squares attacked by enemy pawns are not safe
+        let pos = Position::from_fen("4k3/8/8/8/8/1p6/8/R3K3 w - - 0 1").unwrap();
+        let pawns = PawnEntry::new(&pos);
+        let mobility = mobility_score(&pos, Color::WHITE, &pawns, &mut KingAttack::default());
+        assert_eq!(mobility, ROOK_MOBILITY[9]);
+    }
+
+    fn king_attack_score(fen: &str, color: Color) -> PackedScore {
+        let pos = Position::from_fen(fen).unwrap();
+        let pawns = PawnEntry::new(&pos);
+        let mut attack = KingAttack::default();
+        mobility_score(&pos, color.flip(), &pawns, &mut attack);
+        let enemy_phase = Evaluation::get_material_info(&pos, color.flip()).phase;
+        Evaluation::evaluate_king_attack(&pos, color, &attack, enemy_phase)
+    }
+
+    #[test]
+    fn test_king_attack() {
+        // a lone queen next to the king is not an attack
+        let score = king_attack_score("6k1/5ppp/8/8/8/8/5q2/6K1 w - - 0 1", Color::WHITE);
+        assert_eq!(score, PackedScore::ZERO);
+
+        let single = king_attack_score("6k1/5ppp/8/8/8/8/5q2/r5K1 w - - 0 1", Color::WHITE);
+        let double = king_attack_score("6k1/5ppp/8/8/8/5n2/5q2/r5K1 w - - 0 1", Color::WHITE);
+        assert!(single.mg() < 0);
+        assert!(double.mg() < single.mg(), "more attackers should be more dangerous");
+    }
+
+    #[test]
+    fn test_king_shelter() {
+        let shelter = |fen: &str| {
+            let pos = Position::from_fen(fen).unwrap();
+            Evaluation::evaluate_king_shelter(&PawnEntry::new(&pos), Color::WHITE, Square::G1)
+        };
+        let intact = shelter("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+        let storm = shelter("6k1/5p1p/8/8/8/6p1/5PPP/6K1 w - - 0 1");
+        let open = shelter("6k1/5p1p/8/8/8/8/5P1P/6K1 w - - 0 1");
+        assert_eq!(intact, KING_SHIELD_PAWN * 3);
+        assert_eq!(storm, intact + PAWN_STORM[2]);
+        assert_eq!(open, KING_SHIELD_PAWN * 2 + KING_OPEN_FILE);
+    }
+
+    fn pieces_score(fen: &str, color: Color) -> PackedScore {
+        let pos = Position::from_fen(fen).unwrap();
+        let material = Evaluation::get_material_info(&pos, color);
+        Evaluation::evaluate_pieces(&pos, color, &PawnEntry::new(&pos), &material)
+    }
+
+    #[test]
+    fn test_rook_files() {
+        let open = pieces_score("4k3/pp6/8/8/8/8/PP6/3RK3 w - - 0 1", Color::WHITE);
+        let semi_open = pieces_score("4k3/pp1p4/8/8/8/8/PP6/3RK3 w - - 0 1", Color::WHITE);
+        let closed = pieces_score("4k3/pp6/8/8/8/8/PP1P4/3RK3 w - - 0 1", Color::WHITE);
+        assert_eq!(open, ROOK_ON_OPEN_FILE);
+        assert_eq!(semi_open, ROOK_ON_SEMI_OPEN_FILE);
+        assert_eq!(closed, PackedScore::ZERO);
+
+        let seventh = pieces_score("4k3/pp1R4/8/8/8/8/PP6/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(seventh, ROOK_ON_OPEN_FILE + ROOK_ON_SEVENTH);
+    }
+
+    #[test]
+    fn test_outposts() {
+        // protected by the e4 pawn, and no black pawn can ever chase the knight
+        let outpost = pieces_score("4k3/p7/8/3N4/4P3/8/8/4K3 w - - 0 1", Color::WHITE);
+        let attackable = pieces_score("4k3/p1p5/8/3N4/4P3/8/8/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(outpost, KNIGHT_OUTPOST);
+        assert_eq!(attackable, PackedScore::ZERO);
+    }
+
+    #[test]
+    fn test_bishops() {
+        let pair = pieces_score("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", Color::WHITE);
+        assert_eq!(pair, BISHOP_PAIR);
+
+        // c3 is on a dark square like the bishop, and blocked by the c4 pawn
+        let bad = pieces_score("4k3/8/8/8/2p5/2P5/8/2B1K3 w - - 0 1", Color::WHITE);
+        assert_eq!(bad, BAD_BISHOP_PAWN * 2);
+
+        let trapped = pieces_score("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(trapped, TRAPPED_BISHOP);
+    }
+
+    #[test]
+    fn test_trapped_rook() {
+        let trapped = pieces_score("4k3/8/8/8/8/8/5PPP/5K1R w - - 0 1", Color::WHITE);
+        let castled = pieces_score("4k3/8/8/8/8/8/5PPP/5RK1 w - - 0 1", Color::WHITE);
+        let can_castle = pieces_score("4k3/8/8/8/8/8/5PPP/4K2R w K - 0 1", Color::WHITE);
+        assert_eq!(trapped, TRAPPED_ROOK);
+        assert_eq!(castled, PackedScore::ZERO);
+        assert_eq!(can_castle, PackedScore::ZERO);
+    }
+
+    fn threats_score(fen: &str, color: Color) -> PackedScore {
+        let pos = Position::from_fen(fen).unwrap();
+        let pawns = PawnEntry::new(&pos);
+        let mut attacks = Attacks::new(&pos, color, &pawns);
+        Evaluation::evaluate_mobility(
+            &pos,
+            color,
+            &pawns,
+            &mut KingAttack::default(),
+            &mut attacks,
+        );
+        Evaluation::evaluate_threats(&pos, color, &pawns, &attacks)
+    }
+
+    #[test]
+    fn test_threats() {
+        let hanging = threats_score("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1", Color::WHITE);
+        assert_eq!(hanging, HANGING_PIECE);
+        // the opponent moves first and can save the knight
+        let answered = threats_score("4k3/8/8/3n4/8/8/8/3RK3 b - - 0 1", Color::WHITE);
+        assert_eq!(answered, s(HANGING_PIECE.mg() / 2, HANGING_PIECE.eg() / 2));
+
+        let fork = threats_score("4k3/8/8/2n1b3/3P4/8/8/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(fork, (THREAT_BY_PAWN + HANGING_PIECE) * 2);
+
+        // the rook is defended by the king
+        let minor = threats_score("8/8/3k4/3r4/8/4N3/8/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(minor, MINOR_ATTACKS_ROOK);
+
+        let push = threats_score("4k3/8/8/2n1n3/8/3P4/8/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(push, THREAT_BY_PAWN_PUSH * 2);
+
+        let king = threats_score("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1", Color::WHITE);
+        assert_eq!(king, THREAT_BY_KING);
+    }
+
+    #[test]
+    fn test_imbalance() {
+        let imbalance = |fen: &str| {
+            let pos = Position::from_fen(fen).unwrap();
+            let white = Evaluation::get_material_info(&pos, Color::WHITE);
+            let black = Evaluation::get_material_info(&pos, Color::BLACK);
+            Evaluation::evaluate_imbalance(&white, &black)
+                - Evaluation::evaluate_imbalance(&black, &white)
+        };
+
+        assert_eq!(imbalance(Position::DEFAULT_FEN), PackedScore::ZERO);
+        // knights gain value with pawns on the board
+        let knight_with_pawns = imbalance("4k3/pppppppp/8/8/8/8/PPPPPPPP/1N2K3 w - - 0 1");
+        let knight_alone = imbalance("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1");
+        assert!(knight_with_pawns.mg() > knight_alone.mg());
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        let insufficient = |fen: &str| {
+            let pos = Position::from_fen(fen).unwrap();
+            let white = Evaluation::get_material_info(&pos, Color::WHITE);
+            let black = Evaluation::get_material_info(&pos, Color::BLACK);
+            Evaluation::is_insufficient_material(&white, &black)
+        };
+
+        assert!(insufficient("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"));
+        assert!(insufficient("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1"));
+        assert!(insufficient("4kb2/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"));
+        assert!(!insufficient("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"));
+
+        let pos = Position::from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1").unwrap();
+        let mut eval = Evaluation::new();
+        assert_eq!(eval.evaluate_position(&pos, &mut PawnTable::new()), 0);
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        let scale_factor = |fen: &str, strong: Color| {
+            let pos = Position::from_fen(fen).unwrap();
+            let material = Evaluation::get_material_info(&pos, strong);
+            let enemy_material = Evaluation::get_material_info(&pos, strong.flip());
+            Evaluation::scale_factor(&pos, strong, &material, &enemy_material)
+        };
+
+        let normal = scale_factor("4k3/8/3b4/8/8/2P1B3/8/4K3 w - - 0 1", Color::WHITE);
+        let opposite = scale_factor("4k3/8/3b4/8/8/2PB4/8/4K3 w - - 0 1", Color::WHITE);
+        let with_rooks = scale_factor("r3k3/8/3b4/8/8/2PB4/8/R3K3 w - - 0 1", Color::WHITE);
+        assert_eq!(normal, SCALE_FACTOR_NORMAL);
+        assert_eq!(opposite, OPPOSITE_BISHOPS_SCALE);
+        assert_eq!(with_rooks, OPPOSITE_BISHOPS_WITH_PIECES_SCALE);
+
+        assert_eq!(
+            scale_factor("4k3/8/8/8/8/8/3b4/R3K3 w - - 0 1", Color::WHITE),
+            NO_PAWNS_VS_MINOR_SCALE
+        );
+        let minors = scale_factor("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1", Color::WHITE);
+        assert_eq!(minors, SCALE_FACTOR_NORMAL);
+
+        // a8 is a light square, only a light-squared bishop can help the pawn promote
+        let wrong_bishop = scale_factor("k7/8/8/8/8/8/P7/2B1K3 w - - 0 1", Color::WHITE);
+        let right_bishop = scale_factor("k7/8/8/8/8/8/P7/1B2K3 w - - 0 1", Color::WHITE);
+        let king_away = scale_factor("8/8/8/4k3/8/8/P7/2B1K3 w - - 0 1", Color::WHITE);
+        assert_eq!(wrong_bishop, SCALE_FACTOR_DRAW);
+        assert_eq!(right_bishop, SCALE_FACTOR_NORMAL);
+        assert_eq!(king_away, SCALE_FACTOR_NORMAL);
+    }
+
+    #[test]
+    fn test_mop_up() {
+        let mop_up = |fen: &str, color: Color| {
+            let pos = Position::from_fen(fen).unwrap();
+            let material = Evaluation::get_material_info(&pos, color);
+            let enemy_material = Evaluation::get_material_info(&pos, color.flip());
+            Evaluation::evaluate_mop_up(&pos, color, &material, &enemy_material)
+        };
+
+        let corner = mop_up("k7/8/2K5/8/8/8/8/7R w - - 0 1", Color::WHITE);
+        let center = mop_up("8/8/2K5/8/4k3/8/8/7R w - - 0 1", Color::WHITE);
+        let far = mop_up("k7/8/8/8/8/8/8/6KR w - - 0 1", Color::WHITE);
+        assert_eq!(corner, s(0, 6 * 10 + (14 - 4) * 4));
+        assert!(corner.eg() > center.eg(), "the enemy king should be pushed to the corner");
+        assert!(corner.eg() > far.eg(), "the king should get closer to the enemy king");
+
+        assert_eq!(mop_up("k7/8/2K5/8/8/8/8/7R w - - 0 1", Color::BLACK), PackedScore::ZERO);
+        assert_eq!(mop_up("k7/8/2K5/8/8/8/PP6/8 w - - 0 1", Color::WHITE), PackedScore::ZERO);
     }
 
     #[test]
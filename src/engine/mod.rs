@@ -1,8 +1,10 @@
+mod bitbase;
 mod book;
+mod endgame;
 mod engine;
 mod evaluation;
 mod move_ordering;
-mod piece_square_table;
+mod pawn_table;
 mod search;
 mod ttable;
 
@@ -1,29 +1,31 @@
+use crate::core::material::get_piece_value;
+use crate::core::move_gen::attackers_to;
 use crate::core::position::Position;
 use crate::core::types::*;
-use crate::engine::evaluation::get_piece_value;
 use crate::engine::search::{PVLine, Searcher};
 
 struct ScoredMove {
     mv: Move,
-    score: i16,
+    score: i32,
 }
 
 pub fn sort_moves(
     pos: &Position,
     ctx: &Searcher,
     move_list: &mut MoveList,
-    ply: u8,
+    ply: usize,
     pv_line: &PVLine,
     cached_mv: Move,
 ) {
     fn move_score_guess(
         ctx: &Searcher,
         pos: &Position,
-        ply: u8,
+        ply: usize,
         mv: Move,
         pv_line: &PVLine,
         cached_mv: Move,
-    ) -> i16 {
+        counter_mv: Move,
+    ) -> i32 {
         debug_assert!(mv != Move::null(), "move cannot be null");
 
         // move is in transposition table, give it a high score
@@ -32,16 +34,17 @@ pub fn sort_moves(
         }
 
         // move is the previous depth best move, give it a high score
-        if mv == pv_line[ply as usize] {
+        if mv == pv_line[ply] {
             return 20_000;
         }
 
         //     Priority:
         //   1. TT move             → score 30_000
         //   2. Previous best move  → score 20_000
-        //   3. Killer move         → score 10_000
-        //   4. Capture (MVV-LVA)   → 5_000 + value
-        //   5. Quiet               → 0
+        //   3. Killer move         → score 15_000
+        //   4. Counter move        → score 14_000
+        //   5. Capture (MVV-LVA)   → 5_000 + value
+        //   6. Quiet               → history / 32
 
         let move_type = mv.get_type();
         let src_sq = mv.src_sq();
@@ -55,27 +58,35 @@ pub fn sort_moves(
             pos.get_piece_at(dst_sq)
         };
 
-        // @TODO: killer move ranking
-        if captured_piece == Piece::NONE {
+        let is_quiet = captured_piece == Piece::NONE && move_type != MoveType::Promotion;
+        if is_quiet {
             if ctx.is_killer(ply, mv) {
                 return 15_000; // Killer move
             }
+            if mv == counter_mv {
+                return 14_000; // Counter move
+            }
         }
 
-        let src_piece_value = get_piece_value(src_piece.get_type());
+        let src_piece_value = get_piece_value(src_piece.get_type()) as i32;
 
         let mut score = 0;
 
+        // quiet moves are ordered by how often they caused cut-offs in similar positions
+        if is_quiet {
+            score = ctx.quiet_history(pos, ply, mv) / 32;
+        }
+
         // prioritize capture high value piece with low value piece
         if captured_piece != Piece::NONE {
-            let captured_piece_value = get_piece_value(captured_piece.get_type());
+            let captured_piece_value = get_piece_value(captured_piece.get_type()) as i32;
             score = 10 * captured_piece_value - src_piece_value;
         }
 
         // promote a pawn is also a good move
         if move_type == MoveType::Promotion {
             let promo_piece = mv.get_promotion().unwrap();
-            score += get_piece_value(promo_piece) + 5_000; // promotion bonus
+            score += get_piece_value(promo_piece) as i32 + 5_000; // promotion bonus
         }
 
         if move_type == MoveType::Castling {
@@ -91,11 +102,12 @@ pub fn sort_moves(
         score
     }
 
+    let counter_mv = ctx.counter_move(ply);
     let mut scored: Vec<ScoredMove> = move_list.moves[..move_list.len()]
         .iter()
         .map(|&mv| ScoredMove {
             mv,
-            score: move_score_guess(ctx, pos, ply, mv, pv_line, cached_mv),
+            score: move_score_guess(ctx, pos, ply, mv, pv_line, cached_mv, counter_mv),
         })
         .collect();
 
@@ -105,3 +117,120 @@ pub fn sort_moves(
         move_list.moves[i] = scored_move.mv;
     }
 }
+
+fn least_valuable_attacker(pos: &Position, attackers: BitBoard, color: Color) -> Option<Square> {
+    for i in 0..PieceType::COUNT {
+        let piece = Piece::get_piece(color, PieceType(i));
+        let bitboard = pos.bitboards[piece.as_usize()] & attackers;
+        if let Some(sq) = bitboard.iter().next() {
+            return Some(sq);
+        }
+    }
+    None
+}
+
+/// Static exchange evaluation, the material balance of the capture sequence on the destination
+/// square of `mv`, assuming both sides always recapture with their least valuable attacker and
+/// may stop capturing whenever it's not favorable.
+pub fn static_exchange_eval(pos: &Position, mv: Move) -> i32 {
+    let move_type = mv.get_type();
+    if move_type == MoveType::Castling {
+        return 0;
+    }
+
+    let src_sq = mv.src_sq();
+    let dst_sq = mv.dst_sq();
+    let mut occupancy = pos.state.occupancies[Color::BOTH.as_usize()];
+
+    let mut gain = [0i32; 32];
+    let mut attacker_value = get_piece_value(pos.get_piece_at(src_sq).get_type()) as i32;
+    match move_type {
+        MoveType::EnPassant => {
+            gain[0] = get_piece_value(PieceType::PAWN) as i32;
+            occupancy.unset(mv.get_en_passant_capture().as_u8());
+        }
+        MoveType::Promotion => {
+            let captured = pos.get_piece_at(dst_sq);
+            if captured != Piece::NONE {
+                gain[0] = get_piece_value(captured.get_type()) as i32;
+            }
+            // the promoted piece is the one standing on the square after the move
+            attacker_value = get_piece_value(mv.get_promotion().unwrap()) as i32;
+            gain[0] += attacker_value - get_piece_value(PieceType::PAWN) as i32;
+        }
+        _ => {
+            let captured = pos.get_piece_at(dst_sq);
+            if captured != Piece::NONE {
+                gain[0] = get_piece_value(captured.get_type()) as i32;
+            }
+        }
+    }
+
+    occupancy.unset(src_sq.as_u8());
+    let mut attackers = attackers_to(pos, dst_sq, occupancy) & occupancy;
+    let mut color = pos.side_to_move.flip();
+    let mut depth = 0;
+
+    while let Some(sq) =
+        least_valuable_attacker(pos, attackers & pos.state.occupancies[color.as_usize()], color)
+    {
+        depth += 1;
+        // capture the piece that moved to the square last
+        gain[depth] = attacker_value - gain[depth - 1];
+        if depth == gain.len() - 1 {
+            break;
+        }
+
+        attacker_value = get_piece_value(pos.get_piece_at(sq).get_type()) as i32;
+        occupancy.unset(sq.as_u8());
+        // removing the attacker may reveal sliding pieces behind it
+        attackers = attackers_to(pos, dst_sq, occupancy) & occupancy;
+        color = color.flip();
+    }
+
+    while depth > 0 {
+        gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        depth -= 1;
+    }
+
+    gain[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_see_defended_pawn() {
+        // rook takes an undefended pawn
+        let pos = Position::from_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1").unwrap();
+        let mv = Move::new(Square::E1, Square::E5, MoveType::Normal, None);
+        assert_eq!(static_exchange_eval(&pos, mv), 100);
+
+        // rook takes a pawn defended by another pawn
+        let pos = Position::from_fen("4k3/8/3p4/4p3/8/8/8/4RK2 w - - 0 1").unwrap();
+        let mv = Move::new(Square::E1, Square::E5, MoveType::Normal, None);
+        assert_eq!(static_exchange_eval(&pos, mv), 100 - 500);
+    }
+
+    #[test]
+    fn test_see_xray() {
+        // knight takes a pawn defended by a knight and a rook, backed up by two rooks and a queen
+        let pos =
+            Position::from_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1").unwrap();
+        let mv = Move::new(Square::D3, Square::E5, MoveType::Normal, None);
+        assert_eq!(static_exchange_eval(&pos, mv), 100 - 300);
+    }
+
+    #[test]
+    fn test_see_quiet_move() {
+        let pos = Position::new();
+        let mv = Move::new(Square::E2, Square::E4, MoveType::Normal, None);
+        assert_eq!(static_exchange_eval(&pos, mv), 0);
+
+        // moving the queen to a square attacked by a pawn loses it
+        let pos = Position::from_fen("4k3/8/3p4/8/8/8/8/4QK2 w - - 0 1").unwrap();
+        let mv = Move::new(Square::E1, Square::E5, MoveType::Normal, None);
+        assert_eq!(static_exchange_eval(&pos, mv), -900);
+    }
+}
@@ -0,0 +1,230 @@
+use super::evaluation::{Evaluation, ISOLATED_PAWN_MASKS, PASSED_PAWN_MASKS};
+use crate::core::position::Position;
+use crate::core::score::PackedScore;
+use crate::core::types::*;
+use crate::core::zobrist::ZobristHash;
+
+/// Pawn structure terms of a position, they only depend on the pawns so they are computed once
+/// per pawn structure and cached in the `PawnTable`
+#[derive(Clone, Copy, Debug)]
+pub struct PawnEntry {
+    key: Option<ZobristHash>,
+    pub pawns: [BitBoard; Color::COUNT],
+    pub passed: [BitBoard; Color::COUNT],
+    pub attacks: [BitBoard; Color::COUNT], // squares attacked by pawns
+    pub attack_spans: [BitBoard; Color::COUNT], // squares pawns can attack as they advance
+    pub connected: [BitBoard; Color::COUNT], // pawns supported by a pawn or next to one
+    pub candidates: [BitBoard; Color::COUNT], // pawns that can become passed by advancing
+    pub isolated_count: [u8; Color::COUNT],
+    pub doubled_count: [u8; Color::COUNT],
+    pub backward_count: [u8; Color::COUNT],
+    pub open_files: u8,                      // one bit per file without any pawn
+    pub semi_open_files: [u8; Color::COUNT], // one bit per file without own pawns
+    pub score: [PackedScore; Color::COUNT],  // pawn structure scores
+    shelter: [Option<(Square, PackedScore)>; Color::COUNT], // king square the shield was computed for
+}
+
+impl PawnEntry {
+    const EMPTY: Self = Self {
+        key: None,
+        pawns: [BitBoard::new(); Color::COUNT],
+        passed: [BitBoard::new(); Color::COUNT],
+        attacks: [BitBoard::new(); Color::COUNT],
+        attack_spans: [BitBoard::new(); Color::COUNT],
+        connected: [BitBoard::new(); Color::COUNT],
+        candidates: [BitBoard::new(); Color::COUNT],
+        isolated_count: [0; Color::COUNT],
+        doubled_count: [0; Color::COUNT],
+        backward_count: [0; Color::COUNT],
+        open_files: 0,
+        semi_open_files: [0; Color::COUNT],
+        score: [PackedScore::ZERO; Color::COUNT],
+        shelter: [None; Color::COUNT],
+    };
+
+    pub fn new(pos: &Position) -> Self {
+        let mut entry = Self::EMPTY;
+        entry.key = Some(pos.pawn_key());
+        entry.open_files = 0xFF;
+
+        // the attacks of both sides are needed to find backward pawns
+        for color in [Color::WHITE, Color::BLACK] {
+            let c = color.as_usize();
+            entry.pawns[c] = pos.bitboards[Piece::get_piece(color, PieceType::PAWN).as_usize()];
+            entry.attacks[c] = pawn_attacks(entry.pawns[c], color);
+        }
+
+        for color in [Color::WHITE, Color::BLACK] {
+            let c = color.as_usize();
+            let pawns = entry.pawns[c];
+            let enemy_pawns = entry.pawns[color.flip().as_usize()];
+            let phalanx = pawn_neighbours(pawns);
+
+            let mut files = 0u8;
+            for sq in pawns.iter() {
+                let (file, _) = sq.file_rank();
+                let file_bit = 1u8 << file.0;
+                if files & file_bit != 0 {
+                    entry.doubled_count[c] += 1;
+                }
+                files |= file_bit;
+
+                let front_span = PASSED_PAWN_MASKS[c][sq.as_usize()];
+                let adjacent_files = ISOLATED_PAWN_MASKS[file.0 as usize];
+                let front_file = front_span & !adjacent_files;
+                // own pawns on the adjacent files that are level with or behind this one
+                let supporters = pawns & adjacent_files & !front_span;
+                let sentries = enemy_pawns & front_span & adjacent_files;
+
+                if (enemy_pawns & front_span).none() && (pawns & front_file).none() {
+                    entry.passed[c].set_sq(sq);
+                } else if ((pawns | enemy_pawns) & front_file).none()
+                    && supporters.count() >= sentries.count()
+                {
+                    entry.candidates[c].set_sq(sq);
+                }
+
+                if (pawns & adjacent_files).none() {
+                    entry.isolated_count[c] += 1;
+                } else if supporters.none()
+                    && entry.attacks[color.flip().as_usize()].test_sq(stop_square(color, sq))
+                {
+                    entry.backward_count[c] += 1;
+                }
+
+                if entry.attacks[c].test_sq(sq) || phalanx.test_sq(sq) {
+                    entry.connected[c].set_sq(sq);
+                }
+                entry.attack_spans[c] |= front_span & adjacent_files;
+            }
+
+            entry.open_files &= !files;
+            entry.semi_open_files[c] = !files;
+        }
+
+        entry.score = [
+            Evaluation::evaluate_pawn_structure(&entry, Color::WHITE),
+            Evaluation::evaluate_pawn_structure(&entry, Color::BLACK),
+        ];
+        entry
+    }
+
+    /// Pawn shelter of the king (shield, open files and pawn storms), computed once per king
+    /// square
+    pub fn king_shelter(&mut self, color: Color, king_sq: Square) -> PackedScore {
+        let c = color.as_usize();
+        match self.shelter[c] {
+            Some((sq, score)) if sq == king_sq => score,
+            _ => {
+                let score = Evaluation::evaluate_king_shelter(self, color, king_sq);
+                self.shelter[c] = Some((king_sq, score));
+                score
+            }
+        }
+    }
+}
+
+/// Squares attacked by the pawns of `color`
+pub(super) fn pawn_attacks(pawns: BitBoard, color: Color) -> BitBoard {
+    let pawns = pawns.get();
+    let west = pawns & !BitBoard::MASK_A;
+    let east = pawns & !BitBoard::MASK_H;
+    if color == Color::WHITE {
+        BitBoard::from((west << 7) | (east << 9))
+    } else {
+        BitBoard::from((west >> 9) | (east >> 7))
+    }
+}
+
+/// Squares on the left and right of the pawns
+fn pawn_neighbours(pawns: BitBoard) -> BitBoard {
+    let pawns = pawns.get();
+    BitBoard::from(((pawns & !BitBoard::MASK_A) >> 1) | ((pawns & !BitBoard::MASK_H) << 1))
+}
+
+/// The square in front of a pawn
+pub(super) fn stop_square(color: Color, sq: Square) -> Square {
+    if color == Color::WHITE { Square::new(sq.as_u8() + 8) } else { Square::new(sq.as_u8() - 8) }
+}
+
+/// Cache of pawn structure terms indexed by the pawn key. Every search thread owns a table, so
+/// it's used without synchronization
+pub struct PawnTable {
+    entries: Box<[PawnEntry]>,
+}
+
+impl PawnTable {
+    pub const SIZE: usize = 1 << 13;
+
+    pub fn new() -> Self {
+        Self { entries: vec![PawnEntry::EMPTY; Self::SIZE].into_boxed_slice() }
+    }
+
+    /// Returns the entry for the pawn structure of the position, computing it on a miss
+    pub fn probe(&mut self, pos: &Position) -> &mut PawnEntry {
+        let key = pos.pawn_key();
+        let entry = &mut self.entries[key.0 as usize & (Self::SIZE - 1)];
+        if entry.key != Some(key) {
+            *entry = PawnEntry::new(pos);
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pawn_entry() {
+        let pos = Position::from_fen("4k3/p4pp1/7p/8/1P6/P1P5/8/4K3 w - - 0 1").unwrap();
+        let entry = PawnEntry::new(&pos);
+        let (white, black) = (Color::WHITE.as_usize(), Color::BLACK.as_usize());
+
+        assert_eq!(entry.passed[white].get(), 1u64 << Square::C3.as_u8());
+        assert_eq!(
+            entry.passed[black].get(),
+            1u64 << Square::F7.as_u8() | 1u64 << Square::G7.as_u8() | 1u64 << Square::H6.as_u8()
+        );
+        assert_eq!(entry.isolated_count, [0, 1]);
+        assert_eq!(entry.doubled_count, [0, 0]);
+        assert_eq!(entry.backward_count, [0, 0]);
+        assert_eq!(entry.connected[white].get(), 1u64 << Square::B4.as_u8());
+        assert_eq!(
+            entry.connected[black].get(),
+            1u64 << Square::F7.as_u8() | 1u64 << Square::G7.as_u8() | 1u64 << Square::H6.as_u8()
+        );
+        assert_eq!(entry.candidates[white].get(), 1u64 << Square::B4.as_u8());
+        assert!(entry.candidates[black].none());
+        assert_eq!(entry.open_files, 1 << File::D.0 | 1 << File::E.0);
+        assert_eq!(entry.semi_open_files, [!0b0000_0111, !0b1110_0001]);
+        assert!(entry.attacks[white].test_sq(Square::C5));
+        assert!(entry.attacks[black].test_sq(Square::G5));
+        assert!(entry.attack_spans[white].test_sq(Square::A8));
+        assert!(!entry.attack_spans[white].test_sq(Square::E5));
+    }
+
+    #[test]
+    fn test_backward_pawns() {
+        // d3 is behind its neighbours and d4 is controlled by e5
+        let pos = Position::from_fen("4k3/8/8/4p3/2P1P3/3P4/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(PawnEntry::new(&pos).backward_count, [1, 0]);
+
+        let pos = Position::from_fen("4k3/8/8/4p3/2P1P3/2P5/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(PawnEntry::new(&pos).backward_count, [0, 0]);
+    }
+
+    #[test]
+    fn test_probe_caches_by_pawn_key() {
+        let mut table = PawnTable::new();
+        let pos1 = Position::from_fen("4k3/pp6/8/8/8/8/PP6/4K3 w - - 0 1").unwrap();
+        let pos2 = Position::from_fen("3qk3/pp6/8/8/8/8/PP6/3QK3 b - - 0 1").unwrap();
+
+        let score = table.probe(&pos1).score;
+        assert_eq!(table.probe(&pos2).key, Some(pos1.pawn_key()));
+        assert_eq!(table.probe(&pos2).score, score);
+        // the c, d, e and f files are open
+        assert_eq!(table.probe(&pos1).king_shelter(Color::WHITE, Square::E1).mg(), -60);
+        assert_eq!(table.probe(&pos1).king_shelter(Color::WHITE, Square::B1).mg(), 0);
+    }
+}
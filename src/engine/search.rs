@@ -1,17 +1,42 @@
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::core::material::get_piece_value;
 use crate::core::{move_gen, position::Position, types::*};
-use crate::engine::Engine;
 use crate::engine::book::*;
 use crate::engine::evaluation::Evaluation;
-use crate::engine::move_ordering::sort_moves;
-use crate::engine::ttable::NodeType;
+use crate::engine::move_ordering::{sort_moves, static_exchange_eval};
+use crate::engine::pawn_table::PawnTable;
+use crate::engine::ttable::{EVAL_NONE, NodeType, TTEntry};
+use crate::engine::{Engine, InternalIterative};
 use crate::utils;
 
 const MIN: i32 = i32::MIN + 1; // to avoid overflow when negating
 const MAX: i32 = i32::MAX;
 
 const DRAW_PENALTY: i32 = -50;
-const IMMEDIATE_MATE_SCORE: i32 = 40000;
 const MAX_PLY: usize = 64; // max depth for search, should be enough for most positions
+// Mate scores are relative to the root, mate at ply N scores IMMEDIATE_MATE_SCORE - N.
+// Any score beyond MATE_BOUND is a mate score. Scores must fit in the 16 bits of a TT entry.
+const IMMEDIATE_MATE_SCORE: i32 = 32000;
+const MATE_BOUND: i32 = IMMEDIATE_MATE_SCORE - MAX_PLY as i32;
+const MAX_HISTORY: i32 = 16384; // history scores are kept in [-MAX_HISTORY, MAX_HISTORY]
+const LMR_MIN_DEPTH: u8 = 3;
+const LMR_MIN_MOVES: usize = 3; // TT move, killers and good captures are never reduced
+const MAX_LINE_EXTENSIONS: u8 = 4; // extension budget of a single line from the root
+const SINGULAR_MIN_DEPTH: u8 = 6;
+const IIR_MIN_DEPTH: u8 = 4;
+const IID_MIN_DEPTH: u8 = 5;
+const DELTA_MARGIN: i32 = 200; // safety margin of delta pruning in quiescence search
+const QUIESCENCE_CHECKS: bool = false; // search quiet checks at the first quiescence ply
+const PROBCUT_MIN_DEPTH: u8 = 5;
+const PROBCUT_MARGIN: i32 = 200;
+const PROBCUT_REDUCTION: u8 = 4;
+const NODES_FLUSH_INTERVAL: u64 = 1024; // how often a thread adds its nodes to the shared count
+// Lazy SMP helper threads skip the iterations where ((depth + phase) / size) is odd
+const SKIP_SIZE: [usize; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [usize; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
 // @TODO: add ply optimization, if there are more than 20 plys, it's unlikely to find a book move
 const USE_BOOK: bool = true;
 
@@ -26,8 +51,89 @@ macro_rules! if_debug_search {
 
 pub type PVLine = [Move; MAX_PLY];
 
+/// Converts a root-relative mate score to the node-relative score stored in the TT,
+/// so the entry stays valid when the position is reached at a different ply
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+/// Converts a node-relative TT score back to a score relative to the current root
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+/// Formats a score for the UCI `info` command, either `cp <x>` or `mate <moves>`
+pub fn uci_score(score: i32) -> String {
+    if score >= MATE_BOUND {
+        let plies = IMMEDIATE_MATE_SCORE - score;
+        format!("mate {}", (plies + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        let plies = IMMEDIATE_MATE_SCORE + score;
+        format!("mate -{}", plies / 2)
+    } else {
+        format!("cp {}", score)
+    }
+}
+
+/// Whether the move puts the opponent in check
+fn gives_check(pos: &mut Position, mv: Move) -> bool {
+    let (undo_state, _) = pos.make_move(mv);
+    let gives_check = pos.is_in_check(pos.side_to_move);
+    pos.unmake_move(mv, &undo_state);
+    gives_check
+}
+
+/// History indexed by [piece][to square], one table per (previous piece, previous to square)
+type PieceToHistory = [[i16; 64]; Piece::COUNT];
+
+/// Move made at a given ply, used to index counter moves and continuation history
+#[derive(Copy, Clone)]
+struct StackEntry {
+    piece: Piece,
+    mv: Move,
+    captured: Piece,
+    extensions: u8, // extensions spent on the line leading to the node after this move
+}
+
+impl StackEntry {
+    const NONE: StackEntry =
+        StackEntry { piece: Piece::NONE, mv: Move::null(), captured: Piece::NONE, extensions: 0 };
+}
+
+/// State shared by all the threads of a search
+struct SharedState {
+    stop: AtomicBool,
+    nodes: AtomicU64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SearchResult {
+    mv: Move,
+    score: i32,
+    depth: u8,
+    pv: PVLine, // terminated by a null move
+}
+
 pub struct Searcher {
     killer_moves: [[Option<Move>; 2]; MAX_PLY],
+    history: [[[i16; 64]; 64]; Color::COUNT], // butterfly history, [color][from][to]
+    counter_moves: [[Move; 64]; Piece::COUNT], // [previous piece][previous to square]
+    continuation_history: Box<[PieceToHistory]>, // [previous piece * 64 + previous to square]
+    stack: [StackEntry; MAX_PLY + 1],
+
+    prev_pv: PVLine, // principal variation of the previous iteration
     pv_table: [PVLine; MAX_PLY],
     pv_length: [usize; MAX_PLY],
 
@@ -35,6 +141,14 @@ pub struct Searcher {
     time_limit: f64, // in milliseconds
     cancel: bool,
 
+    nodes: u64,
+    flushed_nodes: u64, // nodes already added to the shared count
+
+    thread_id: usize, // 0 is the main thread
+    shared: Arc<SharedState>,
+
+    pawn_table: PawnTable,
+
     // for debugging purposes
     pruned_count: u64,
     total_moves: u64,
@@ -43,23 +157,64 @@ pub struct Searcher {
 
 impl Searcher {
     pub fn new(time_limit: f64) -> Self {
+        let shared = SharedState { stop: AtomicBool::new(false), nodes: AtomicU64::new(0) };
+        Self::new_thread(time_limit, 0, Arc::new(shared))
+    }
+
+    fn new_thread(time_limit: f64, thread_id: usize, shared: Arc<SharedState>) -> Self {
         Self {
             killer_moves: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; Color::COUNT],
+            counter_moves: [[Move::null(); 64]; Piece::COUNT],
+            continuation_history: vec![[[0; 64]; Piece::COUNT]; Piece::COUNT * 64]
+                .into_boxed_slice(),
+            stack: [StackEntry::NONE; MAX_PLY + 1],
+            prev_pv: [Move::null(); MAX_PLY],
             pv_table: [[Move::null(); MAX_PLY]; MAX_PLY],
             pv_length: [0; MAX_PLY],
             timer: utils::Timer::new(),
             time_limit,
             cancel: false,
+            nodes: 0,
+            flushed_nodes: 0,
+            thread_id,
+            shared,
+            pawn_table: PawnTable::new(),
             pruned_count: 0,
             total_moves: 0,
             leaf_count: 0,
         }
     }
 
+    /// Nodes searched by all the threads
+    pub fn nodes(&self) -> u64 {
+        self.total_nodes()
+    }
+
+    fn total_nodes(&self) -> u64 {
+        self.shared.nodes.load(Ordering::Relaxed) + self.nodes - self.flushed_nodes
+    }
+
+    fn flush_nodes(&mut self) {
+        self.shared.nodes.fetch_add(self.nodes - self.flushed_nodes, Ordering::Relaxed);
+        self.flushed_nodes = self.nodes;
+    }
+
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if self.nodes - self.flushed_nodes >= NODES_FLUSH_INTERVAL {
+            self.flush_nodes();
+        }
+    }
+
     pub fn should_cancel(&mut self) -> bool {
         if self.cancel {
             return true;
         }
+        if self.shared.stop.load(Ordering::Relaxed) {
+            self.cancel = true;
+            return true;
+        }
         if self.timer.elapsed_ms() >= self.time_limit {
             log::debug!("Time limit reached, cancelling search");
             self.cancel = true;
@@ -68,17 +223,140 @@ impl Searcher {
         false
     }
 
-    // @TODO: review killer
-    fn add_killer(&mut self, ply: u8, mv: Move) {
-        let killers = &mut self.killer_moves[ply as usize];
+    fn add_killer(&mut self, ply: usize, mv: Move) {
+        let killers = &mut self.killer_moves[ply];
         if killers[0] != Some(mv) {
             killers[1] = killers[0];
             killers[0] = Some(mv);
         }
     }
 
-    pub fn is_killer(&self, ply: u8, mv: Move) -> bool {
-        self.killer_moves[ply as usize].contains(&Some(mv))
+    pub fn is_killer(&self, ply: usize, mv: Move) -> bool {
+        self.killer_moves[ply].contains(&Some(mv))
+    }
+
+    /// Returns the move played `back` plies before the node at `ply`, if any
+    fn prev_entry(&self, ply: usize, back: usize) -> Option<StackEntry> {
+        if ply < back {
+            return None;
+        }
+        let entry = self.stack[ply - back];
+        if entry.piece == Piece::NONE { None } else { Some(entry) }
+    }
+
+    /// The quiet move that refuted the opponent's last move the last time it was played
+    pub fn counter_move(&self, ply: usize) -> Move {
+        match self.prev_entry(ply, 1) {
+            Some(prev) => self.counter_moves[prev.piece.as_usize()][prev.mv.dst_sq().as_usize()],
+            None => Move::null(),
+        }
+    }
+
+    /// Combined butterfly and continuation history score of a quiet move
+    pub fn quiet_history(&self, pos: &Position, ply: usize, mv: Move) -> i32 {
+        let piece = pos.get_piece_at(mv.src_sq());
+        let src = mv.src_sq().as_usize();
+        let dst = mv.dst_sq().as_usize();
+
+        let mut score = self.history[pos.side_to_move.as_usize()][src][dst] as i32;
+        for back in 1..=2 {
+            if let Some(prev) = self.prev_entry(ply, back) {
+                let idx = prev.piece.as_usize() * 64 + prev.mv.dst_sq().as_usize();
+                score += self.continuation_history[idx][piece.as_usize()][dst] as i32;
+            }
+        }
+        score
+    }
+
+    /// Gravity update, large bonuses are damped as the entry approaches MAX_HISTORY
+    fn apply_history_bonus(entry: &mut i16, bonus: i32) {
+        let bonus = bonus.clamp(-MAX_HISTORY, MAX_HISTORY);
+        let value = *entry as i32;
+        *entry = (value + bonus - value * bonus.abs() / MAX_HISTORY) as i16;
+    }
+
+    fn update_quiet_history(&mut self, pos: &Position, ply: usize, mv: Move, bonus: i32) {
+        let piece = pos.get_piece_at(mv.src_sq());
+        let src = mv.src_sq().as_usize();
+        let dst = mv.dst_sq().as_usize();
+
+        Self::apply_history_bonus(&mut self.history[pos.side_to_move.as_usize()][src][dst], bonus);
+        for back in 1..=2 {
+            if let Some(prev) = self.prev_entry(ply, back) {
+                let idx = prev.piece.as_usize() * 64 + prev.mv.dst_sq().as_usize();
+                let entry = &mut self.continuation_history[idx][piece.as_usize()][dst];
+                Self::apply_history_bonus(entry, bonus);
+            }
+        }
+    }
+
+    /// Called when a quiet move causes a beta cut-off, the quiet moves tried before it get a malus
+    fn update_quiet_stats(
+        &mut self,
+        pos: &Position,
+        ply: usize,
+        depth: u8,
+        best_move: Move,
+        quiets_tried: &MoveList,
+    ) {
+        let bonus = (depth as i32 * depth as i32).min(400) * 8;
+
+        self.add_killer(ply, best_move);
+        self.update_quiet_history(pos, ply, best_move, bonus);
+        for mv in quiets_tried.iter().copied() {
+            if mv != best_move {
+                self.update_quiet_history(pos, ply, mv, -bonus);
+            }
+        }
+
+        if let Some(prev) = self.prev_entry(ply, 1) {
+            self.counter_moves[prev.piece.as_usize()][prev.mv.dst_sq().as_usize()] = best_move;
+        }
+    }
+
+    /// Extensions already spent on the line leading to the node at `ply`
+    fn line_extensions(&self, ply: usize) -> u8 {
+        if ply == 0 { 0 } else { self.stack[ply - 1].extensions }
+    }
+
+    /// Returns how many plies the move just made at `ply` should be extended by.
+    /// Only one extension is granted per move, and none once the line has used up its budget.
+    fn extension(&self, pos: &Position, ply: usize, is_pv: bool, singular: bool) -> u8 {
+        if self.line_extensions(ply) >= MAX_LINE_EXTENSIONS {
+            return 0;
+        }
+
+        if singular {
+            return 1;
+        }
+
+        // the move is already made, so side_to_move is the opponent. Every check is extended, the
+        // line budget keeps series of spite checks from blowing up the search
+        let entry = self.stack[ply];
+        if pos.is_in_check(pos.side_to_move) {
+            return 1;
+        }
+
+        // recapture on the square where the opponent just captured
+        if is_pv
+            && entry.captured != Piece::NONE
+            && let Some(prev) = self.prev_entry(ply, 1)
+            && prev.captured != Piece::NONE
+            && prev.mv.dst_sq() == entry.mv.dst_sq()
+        {
+            return 1;
+        }
+
+        // pawn push to the 7th rank, one step away from promotion
+        if entry.piece.get_type() == PieceType::PAWN {
+            let (_, rank) = entry.mv.dst_sq().file_rank();
+            let seventh = if entry.piece.color() == Color::WHITE { Rank::_7 } else { Rank::_2 };
+            if rank == seventh {
+                return 1;
+            }
+        }
+
+        0
     }
 
     fn evaluate(&mut self, pos: &Position) -> i32 {
@@ -86,32 +364,104 @@ impl Searcher {
 
         let mut eval = Evaluation::new();
         // @TODO: change to i16
-        eval.evaluate_position(pos) as i32
+        eval.evaluate_position(pos, &mut self.pawn_table) as i32
     }
 
-    fn quiescence(&mut self, engine: &mut Engine, mut alpha: i32, beta: i32, depth: i32) -> i32 {
+    /// Searches captures until the position is quiet, so that the static evaluation isn't taken in
+    /// the middle of an exchange. When in check all evasions are searched instead, and at the first
+    /// ply quiet checks are searched as well if `quiet_checks` is set.
+    fn quiescence(
+        &mut self,
+        engine: &mut Engine,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        quiet_checks: bool,
+    ) -> i32 {
         if self.should_cancel() {
             return 0;
         }
+        self.count_node();
+        self.pv_length[ply] = 0;
 
-        let eval = self.evaluate(&engine.state.pos);
-        if eval >= beta {
-            // searchDiagnostics.numCutOffs++;
-            return beta;
+        let side_to_move = engine.state.pos.side_to_move;
+        let in_check = engine.state.pos.is_in_check(side_to_move);
+        if ply >= MAX_PLY - 1 {
+            return if in_check { 0 } else { self.evaluate(&engine.state.pos) };
         }
-        if eval > alpha {
-            alpha = eval;
+
+        let key = *engine.state.zobrist_stack.last().unwrap();
+        let alpha_orig = alpha;
+        let mut cached_move = Move::null();
+        let tt_hit = engine.tt.probe(key);
+        if let Some(entry) = tt_hit {
+            let score = score_from_tt(entry.score, ply);
+            let cutoff = match entry.node_type {
+                NodeType::Exact => true,
+                NodeType::LowerBound => score >= beta,
+                NodeType::UpperBound => score <= alpha,
+            };
+            if cutoff {
+                return score;
+            }
+            cached_move = entry.best_move;
         }
-        // because we can't cancel the search, add a depth parameter
-        if depth == 0 {
-            return eval;
+
+        // Stand pat: the side to move can usually do at least as well as the static evaluation by
+        // not capturing, except when it's in check
+        let mut best_score = MIN;
+        let mut eval = EVAL_NONE;
+        if !in_check {
+            eval = match tt_hit {
+                Some(entry) if entry.static_eval != EVAL_NONE => entry.static_eval,
+                _ => self.evaluate(&engine.state.pos),
+            };
+            if eval >= beta {
+                return eval;
+            }
+            alpha = alpha.max(eval);
+            best_score = eval;
         }
 
-        let move_list = move_gen::pseudo_legal_capture_moves(&engine.state.pos);
+        let mut move_list = if in_check {
+            move_gen::pseudo_legal_moves(&engine.state.pos)
+        } else if quiet_checks {
+            let pos = &mut engine.state.pos;
+            let mut move_list = move_gen::pseudo_legal_capture_moves(pos);
+            for mv in move_gen::pseudo_legal_moves(pos).iter().copied() {
+                if mv.get_type() == MoveType::Normal
+                    && pos.get_piece_at(mv.dst_sq()) == Piece::NONE
+                    && gives_check(pos, mv)
+                {
+                    move_list.add(mv);
+                }
+            }
+            move_list
+        } else {
+            move_gen::pseudo_legal_capture_moves(&engine.state.pos)
+        };
+        sort_moves(&engine.state.pos, self, &mut move_list, ply, &self.prev_pv, cached_move);
 
+        let mut best_move = Move::null();
         let mut has_legal_moves = false;
-        let side_to_move = engine.state.pos.side_to_move;
         for mv in move_list.iter().copied() {
+            let src_piece = engine.state.pos.get_piece_at(mv.src_sq());
+            let captured_piece = engine.state.pos.get_piece_at(mv.dst_sq());
+
+            if !in_check {
+                // Delta pruning: even winning the captured piece for free can't raise alpha
+                if mv.get_type() != MoveType::Promotion && captured_piece != Piece::NONE {
+                    let gain = get_piece_value(captured_piece.get_type()) as i32;
+                    if eval + gain + DELTA_MARGIN <= alpha {
+                        continue;
+                    }
+                }
+                // losing captures and checks that hang the piece are not worth searching
+                if static_exchange_eval(&engine.state.pos, mv) < 0 {
+                    continue;
+                }
+            }
+
             let (undo_state, ok) = engine.state.pos.make_move(mv);
             if !ok {
                 engine.state.pos.unmake_move(mv, &undo_state);
@@ -119,57 +469,159 @@ impl Searcher {
             }
 
             has_legal_moves = true;
+            self.stack[ply] = StackEntry {
+                piece: src_piece,
+                mv,
+                captured: captured_piece,
+                extensions: self.line_extensions(ply),
+            };
 
             engine.state.push_zobrist();
-            let score = -self.quiescence(engine, -beta, -alpha, depth - 1);
+            let score = -self.quiescence(engine, ply + 1, -beta, -alpha, false);
 
             if self.should_cancel() {
                 return 0; // cancel the search
             }
 
             engine.state.pop_zobrist();
-
             engine.state.pos.unmake_move(mv, &undo_state);
 
-            if score >= beta {
-                // @TODO: stats
-                return beta;
+            if score > best_score {
+                best_score = score;
+                best_move = mv;
             }
-            if score > alpha {
-                alpha = score;
+            if score >= beta {
+                break;
             }
+            alpha = alpha.max(score);
         }
 
-        // @TODO: revisit this logic, might want to add ply to it
-        if !has_legal_moves {
-            if engine.state.pos.is_in_check(side_to_move) {
-                return -(IMMEDIATE_MATE_SCORE + depth);
+        if in_check && !has_legal_moves {
+            return -(IMMEDIATE_MATE_SCORE - ply as i32);
+        }
+
+        if !best_move.is_null() {
+            let node_type = if best_score >= beta {
+                NodeType::LowerBound
+            } else if best_score > alpha_orig {
+                NodeType::Exact
             } else {
-                return DRAW_PENALTY;
+                NodeType::UpperBound
+            };
+            let entry = TTEntry {
+                score: score_to_tt(best_score, ply),
+                static_eval: eval,
+                best_move,
+                depth: 0,
+                node_type,
+                is_pv: tt_hit.is_some_and(|entry| entry.is_pv),
+            };
+            engine.tt.store(key, entry);
+        }
+
+        best_score
+    }
+
+    /// Searches the captures whose static exchange beats `probcut_beta` with a reduced depth and a
+    /// null window, returns the score and the move of the first one that fails high.
+    fn probcut(
+        &mut self,
+        engine: &mut Engine,
+        ply: usize,
+        ply_remaining: u8,
+        probcut_beta: i32,
+        static_eval: i32,
+        cached_move: Move,
+    ) -> Option<(i32, Move)> {
+        let see_threshold = probcut_beta - static_eval;
+
+        let mut move_list = move_gen::pseudo_legal_capture_moves(&engine.state.pos);
+        sort_moves(&engine.state.pos, self, &mut move_list, ply, &self.prev_pv, cached_move);
+
+        for mv in move_list.iter().copied() {
+            if static_exchange_eval(&engine.state.pos, mv) < see_threshold {
+                continue;
+            }
+
+            let src_piece = engine.state.pos.get_piece_at(mv.src_sq());
+            let captured_piece = engine.state.pos.get_piece_at(mv.dst_sq());
+            let (undo_state, ok) = engine.state.pos.make_move(mv);
+            if !ok {
+                engine.state.pos.unmake_move(mv, &undo_state);
+                continue;
+            }
+
+            self.stack[ply] = StackEntry {
+                piece: src_piece,
+                mv,
+                captured: captured_piece,
+                extensions: self.line_extensions(ply),
+            };
+            engine.state.push_zobrist();
+
+            // a quick quiescence search filters out the captures that don't hold up
+            let mut score =
+                -self.quiescence(engine, ply + 1, -probcut_beta, -probcut_beta + 1, false);
+            if score >= probcut_beta {
+                let (verified_score, _) = self.negamax(
+                    engine,
+                    ply + 1,
+                    ply_remaining - PROBCUT_REDUCTION,
+                    -probcut_beta,
+                    -probcut_beta + 1,
+                    Move::null(),
+                );
+                score = -verified_score;
+            }
+
+            // results of a cancelled search are meaningless, don't let them into the TT
+            if self.should_cancel() {
+                return None;
+            }
+
+            engine.state.pop_zobrist();
+            engine.state.pos.unmake_move(mv, &undo_state);
+
+            if score >= probcut_beta {
+                let key = *engine.state.zobrist_stack.last().unwrap();
+                let entry = TTEntry {
+                    score: score_to_tt(score, ply),
+                    static_eval,
+                    best_move: mv,
+                    depth: ply_remaining - PROBCUT_REDUCTION + 1,
+                    node_type: NodeType::LowerBound,
+                    is_pv: false,
+                };
+                engine.tt.store(key, entry);
+                return Some((score, mv));
             }
         }
 
-        alpha
+        None
     }
 
     fn negamax(
         &mut self,
         engine: &mut Engine,
-        max_ply: u8,
-        ply_remaining: u8,
+        ply: usize,
+        mut ply_remaining: u8,
         mut alpha: i32,
         mut beta: i32,
-        pv_line: &PVLine,
+        excluded_move: Move,
     ) -> (i32, Move) {
         if self.should_cancel() {
             return (0, Move::null());
         }
+        self.count_node();
 
         let key = *engine.state.zobrist_stack.last().unwrap();
-        let alpha_orig = alpha;
+        let is_pv = beta > alpha + 1;
+        // a singular verification search excludes the TT move, so it must not use or pollute the
+        // TT entry of the full node
+        let is_exclusion_search = !excluded_move.is_null();
 
         // --- 1) Check for repetition and 50-move rule ---
-        if max_ply > ply_remaining {
+        if ply > 0 {
             if engine.state.is_three_fold() {
                 log::debug!("repetition detected at depth: {}", ply_remaining);
                 return (DRAW_PENALTY, Move::null());
@@ -179,17 +631,32 @@ impl Searcher {
                 log::debug!("50-move rule draw detected: {}", engine.state.pos.fen());
                 return (DRAW_PENALTY, Move::null());
             }
+
+            // Mate distance pruning: even mating on the next move can't beat a shorter mate
+            // already found, and being mated here can't be worse than a mate already found
+            alpha = alpha.max(-(IMMEDIATE_MATE_SCORE - ply as i32));
+            beta = beta.min(IMMEDIATE_MATE_SCORE - ply as i32 - 1);
+            if alpha >= beta {
+                return (alpha, Move::null());
+            }
         }
+        // taken after the mate distance clamp, so the stored bound is relative to the window the
+        // node was actually searched with
+        let alpha_orig = alpha;
 
         // --- 2) Probe transposition table ---
         let mut cached_move = Move::null();
-        if let Some(entry) = engine.tt.probe(key) {
-            if entry.depth >= ply_remaining {
+        let mut tt_hit = None;
+        if let Some(mut entry) = engine.tt.probe(key) {
+            entry.score = score_from_tt(entry.score, ply);
+            // no cutoff at the root, the PV must come from this search, not from an entry stored
+            // by another thread
+            if ply > 0 && entry.depth >= ply_remaining && !is_exclusion_search {
                 let mut found = false;
                 match entry.node_type {
                     NodeType::Exact => found = true,
-                    NodeType::LowerBound => alpha = alpha.max(entry.score as i32),
-                    NodeType::UpperBound => beta = beta.min(entry.score as i32),
+                    NodeType::LowerBound => alpha = alpha.max(entry.score),
+                    NodeType::UpperBound => beta = beta.min(entry.score),
                 }
                 if found || alpha >= beta {
                     return (entry.score, entry.best_move);
@@ -200,28 +667,133 @@ impl Searcher {
                 !cached_move.is_null(),
                 "Transposition table entry should have a best move"
             );
+            tt_hit = Some(entry);
         }
 
         // --- 3) Check depth cutoff (leaf node) ---
-        if ply_remaining == 0 {
-            return (self.quiescence(engine, alpha, beta, 4), Move::null());
+        if ply_remaining == 0 || ply >= MAX_PLY - 1 {
+            let score = self.quiescence(engine, ply, alpha, beta, QUIESCENCE_CHECKS);
+            return (score, Move::null());
+        }
+
+        // --- 4) Internal iterative reductions/deepening ---
+        // Without a hash move the move ordering is poor, either spend less effort on the node, which
+        // is likely not important, or run a shallower search to find a move to try first
+        if cached_move.is_null() && !is_exclusion_search {
+            match engine.options.internal_iterative {
+                InternalIterative::Reduction if ply_remaining >= IIR_MIN_DEPTH => {
+                    ply_remaining -= 1;
+                }
+                InternalIterative::Deepening if is_pv && ply_remaining >= IID_MIN_DEPTH => {
+                    let (_, mv) =
+                        self.negamax(engine, ply, ply_remaining - 2, alpha, beta, Move::null());
+                    if self.should_cancel() {
+                        return (0, Move::null());
+                    }
+                    cached_move = mv;
+                }
+                _ => {}
+            }
+        }
+
+        let side_to_move = engine.state.pos.side_to_move;
+        let in_check = engine.state.pos.is_in_check(side_to_move);
+        // the static evaluation is reused from the TT if the position was evaluated before,
+        // otherwise it is only computed when a pruning decision needs it
+        let mut static_eval = match tt_hit {
+            Some(entry) if !in_check => entry.static_eval,
+            _ => EVAL_NONE,
+        };
+        // positions that were on the PV once are likely to be again, they are not cut by ProbCut
+        let tt_pv = is_pv || tt_hit.is_some_and(|entry| entry.is_pv);
+
+        // --- 5) ProbCut ---
+        // If a good capture beats beta by a margin in a reduced-depth search, the full-depth
+        // search would very likely fail high as well, so the node can be cut early
+        let probcut_beta = beta.saturating_add(PROBCUT_MARGIN);
+        if !tt_pv
+            && ply > 0
+            && !in_check
+            && !is_exclusion_search
+            && ply_remaining >= PROBCUT_MIN_DEPTH
+            && beta.abs() < MATE_BOUND
+            // a TT entry searched almost as deep already tells the capture won't be enough
+            && !tt_hit.is_some_and(|entry| {
+                entry.depth + 3 >= ply_remaining && entry.score < probcut_beta
+            })
+        {
+            if static_eval == EVAL_NONE {
+                static_eval = self.evaluate(&engine.state.pos);
+            }
+            if let Some((score, mv)) =
+                self.probcut(engine, ply, ply_remaining, probcut_beta, static_eval, cached_move)
+            {
+                return (score, mv);
+            }
+            if self.should_cancel() {
+                return (0, Move::null());
+            }
+        }
+
+        // --- 6) Singular extension ---
+        // If the TT move is much better than every alternative searched at reduced depth, it is
+        // "singular" and gets extended. If even the alternatives beat beta, several moves fail high
+        // and the node can be cut (multi-cut).
+        let mut singular_move = Move::null();
+        if let Some(entry) = tt_hit
+            && ply > 0
+            && !is_exclusion_search
+            && ply_remaining >= SINGULAR_MIN_DEPTH
+            && entry.depth + 3 >= ply_remaining
+            && !matches!(entry.node_type, NodeType::UpperBound)
+            && entry.score.abs() < MATE_BOUND
+        {
+            let singular_beta = entry.score - 2 * ply_remaining as i32;
+            let singular_depth = (ply_remaining - 1) / 2;
+            let (score, _) = self.negamax(
+                engine,
+                ply,
+                singular_depth,
+                singular_beta - 1,
+                singular_beta,
+                entry.best_move,
+            );
+            if self.should_cancel() {
+                return (0, Move::null());
+            }
+
+            if score < singular_beta {
+                singular_move = entry.best_move;
+            } else if singular_beta >= beta {
+                return (singular_beta, Move::null());
+            }
         }
 
         // @NOTE: we pseudo-legal moves here for speed, the illegal moves will be filtered out later
         let mut move_list = move_gen::pseudo_legal_moves(&engine.state.pos);
 
-        // --- 4) Move ordering ---
-        sort_moves(&engine.state.pos, &self, &mut move_list, ply_remaining, pv_line, cached_move);
+        // --- 7) Move ordering ---
+        sort_moves(&engine.state.pos, self, &mut move_list, ply, &self.prev_pv, cached_move);
         let mut best_move = Move::null();
         let mut best_score = MIN;
 
-        let ply = (max_ply - ply_remaining) as usize;
-
-        // --- 5) Main search loop ---
+        // --- 8) Main search loop ---
         let mut has_legal_moves = false;
+        let mut moves_searched = 0;
+        let mut quiets_tried = MoveList::new();
         let mut mv_left = move_list.len();
-        let side_to_move = engine.state.pos.side_to_move.clone();
         for mv in move_list.iter().copied() {
+            if mv == excluded_move {
+                mv_left -= 1;
+                continue;
+            }
+
+            let src_piece = engine.state.pos.get_piece_at(mv.src_sq());
+            let captured_piece = engine.state.pos.get_piece_at(mv.dst_sq());
+            let is_quiet = captured_piece == Piece::NONE
+                && matches!(mv.get_type(), MoveType::Normal | MoveType::Castling);
+            let history = if is_quiet { self.quiet_history(&engine.state.pos, ply, mv) } else { 0 };
+
             let (undo_state, ok) = engine.state.pos.make_move(mv);
             if !ok {
                 engine.state.pos.unmake_move(mv, &undo_state);
@@ -229,27 +801,66 @@ impl Searcher {
             }
 
             has_legal_moves = true;
-            let captured_piece = engine.state.pos.state.captured_piece;
+            let gives_check = engine.state.pos.is_in_check(engine.state.pos.side_to_move);
+
+            self.stack[ply] = StackEntry {
+                piece: src_piece,
+                mv,
+                captured: captured_piece,
+                extensions: self.line_extensions(ply),
+            };
+            let extension =
+                self.extension(&engine.state.pos, ply, is_pv, mv == singular_move && !mv.is_null());
+            self.stack[ply].extensions += extension;
+            let new_depth = ply_remaining - 1 + extension;
 
             engine.state.push_zobrist();
-            let (score, _) =
-                self.negamax(engine, max_ply, ply_remaining - 1, -beta, -alpha, pv_line);
+
+            // Late move reductions: quiet moves ordered late are searched with a reduced depth
+            // and a null window first, and only re-searched at full depth if they beat alpha
+            let mut reduction = 0;
+            if ply_remaining >= LMR_MIN_DEPTH
+                && moves_searched >= LMR_MIN_MOVES
+                && is_quiet
+                && !in_check
+                && !gives_check
+            {
+                reduction = if moves_searched >= 6 { 2 } else { 1 };
+                if history > MAX_HISTORY / 2 {
+                    reduction -= 1;
+                } else if history < -MAX_HISTORY / 2 {
+                    reduction += 1;
+                }
+                reduction = reduction.min(new_depth - 1);
+            }
+
+            let mut score = MIN;
+            if reduction > 0 {
+                let (reduced_score, _) = self.negamax(
+                    engine,
+                    ply + 1,
+                    new_depth - reduction,
+                    -alpha - 1,
+                    -alpha,
+                    Move::null(),
+                );
+                score = -reduced_score;
+            }
+            if reduction == 0 || score > alpha {
+                let (full_score, _) =
+                    self.negamax(engine, ply + 1, new_depth, -beta, -alpha, Move::null());
+                score = -full_score;
+            }
 
             if self.should_cancel() {
                 return (0, Move::null()); // cancel the search
             }
 
-            let score = -score; // Negate the score for the opponent
-
             engine.state.pop_zobrist();
             engine.state.pos.unmake_move(mv, &undo_state);
+            moves_searched += 1;
 
             if score > best_score {
-                if mv.get_type() == MoveType::Normal && captured_piece == Piece::NONE {
-                    // this is a quiet move, so we can add it to the killer moves
-                    self.add_killer(ply_remaining, mv);
-                }
-
                 // because we updated alpha every search,
                 // from now on all moves will have at least alpha score
                 // so we can only update best_move if score is strictly better than previous score
@@ -267,18 +878,35 @@ impl Searcher {
 
             alpha = alpha.max(score);
             if alpha >= beta {
+                if is_quiet {
+                    self.update_quiet_stats(
+                        &engine.state.pos,
+                        ply,
+                        ply_remaining,
+                        mv,
+                        &quiets_tried,
+                    );
+                }
                 break; // beta cut-off
             }
+            if is_quiet {
+                quiets_tried.add(mv);
+            }
             mv_left -= 1;
         }
 
-        // --- 6) Check for terminal node (mate/stalemate) ---
+        // --- 9) Check for terminal node (mate/stalemate) ---
         if !has_legal_moves {
-            let score = if engine.state.pos.is_in_check(side_to_move) {
+            // the only legal move was excluded, this is not a terminal node
+            if is_exclusion_search {
+                return (alpha, Move::null());
+            }
+
+            let score = if in_check {
                 // shallower checkmate should have higher score
                 // because it's a position where the side to move is losing,
                 // so we negate the score
-                -(IMMEDIATE_MATE_SCORE + ply_remaining as i32)
+                -(IMMEDIATE_MATE_SCORE - ply as i32)
             } else {
                 DRAW_PENALTY
             };
@@ -288,7 +916,7 @@ impl Searcher {
         self.pruned_count += mv_left as u64;
         self.total_moves += move_list.len() as u64;
 
-        // --- 7) Store result in transposition table ---
+        // --- 10) Store result in transposition table ---
         let node_type = if best_score <= alpha_orig {
             NodeType::UpperBound
         } else if best_score >= beta {
@@ -298,7 +926,17 @@ impl Searcher {
         };
 
         debug_assert!(!best_move.is_null(), "Best move should be valid");
-        engine.tt.store(key, ply_remaining, best_score, node_type, best_move);
+        if !is_exclusion_search {
+            let entry = TTEntry {
+                score: score_to_tt(best_score, ply),
+                static_eval,
+                best_move,
+                depth: ply_remaining,
+                node_type,
+                is_pv: tt_pv,
+            };
+            engine.tt.store(key, entry);
+        }
 
         (best_score, best_move)
     }
@@ -324,7 +962,49 @@ impl Searcher {
 
     pub fn find_best_move_depth(&mut self, engine: &mut Engine, max_depth: u8) -> Option<Move> {
         debug_assert!(max_depth > 0, "Depth should be greater than 0");
+        self.search(engine, &mut std::io::sink(), max_depth)
+    }
+
+    /// Principal variation of the last search from the root
+    fn root_pv(&self) -> PVLine {
+        let mut pv = [Move::null(); MAX_PLY];
+        let len = self.pv_length[0];
+        pv[..len].copy_from_slice(&self.pv_table[0][..len]);
+        pv
+    }
+
+    /// Reports a finished iteration with a UCI `info` line
+    fn write_info<W: Write>(&self, engine: &Engine, writer: &mut W, result: &SearchResult) {
+        let nodes = self.total_nodes();
+        let elapsed_ms = self.timer.elapsed_ms();
+        writeln!(
+            writer,
+            "info depth {} score {} nodes {} nps {} hashfull {} time {} pv {}",
+            result.depth,
+            uci_score(result.score),
+            nodes,
+            (nodes as f64 * 1000.0 / elapsed_ms.max(1.0)) as u64,
+            engine.tt.hashfull(),
+            elapsed_ms as u64,
+            pv_string(&result.pv)
+        )
+        .unwrap();
+    }
 
+    pub fn find_best_move<W: Write>(
+        &mut self,
+        engine: &mut Engine,
+        writer: &mut W,
+    ) -> Option<Move> {
+        self.search(engine, writer, MAX_PLY as u8 - 1)
+    }
+
+    fn search<W: Write>(
+        &mut self,
+        engine: &mut Engine,
+        writer: &mut W,
+        max_depth: u8,
+    ) -> Option<Move> {
         // @TODO: fix it?
         let move_list = move_gen::legal_moves(&mut engine.state.pos);
         if move_list.is_empty() {
@@ -338,97 +1018,352 @@ impl Searcher {
             }
         }
 
-        let mut best_move = Move::null();
-
-        for depth in 1..=max_depth {
-            self.total_moves = 0;
-            self.pruned_count = 0;
-            self.leaf_count = 0;
+        engine.tt.new_search();
+        let threads = if cfg!(target_arch = "wasm32") { 1 } else { engine.options.threads.max(1) };
+        let result = if threads == 1 {
+            self.iterative_deepening(engine, writer, max_depth)
+        } else {
+            self.lazy_smp(engine, writer, max_depth, threads)
+        };
 
-            let prev_pv = self.pv_table[0];
-            let (_, mv) = self.negamax(engine, depth, depth, MIN, MAX, &prev_pv);
+        if_debug_search!({
+            let moves = pv_string(&self.root_pv());
 
-            best_move = mv;
-        }
+            log::debug!(
+                "moves: {} (score: {}) found in {} ms, at depth: {}, {} leaves evaluated, {}/{} ({}%) pruned",
+                moves,
+                result.map_or(MIN, |r| r.score),
+                self.timer.elapsed_ms(),
+                result.map_or(0, |r| r.depth),
+                self.leaf_count,
+                self.pruned_count,
+                self.total_moves,
+                self.pruned_count as f32 / self.total_moves as f32 * 100.0
+            );
+        });
 
-        return Some(best_move);
+        result.map(|r| r.mv)
     }
 
-    pub fn find_best_move(&mut self, engine: &mut Engine) -> Option<Move> {
-        // @TODO: fix it?
-        let move_list = move_gen::legal_moves(&mut engine.state.pos);
-        if move_list.is_empty() {
-            return None;
-        }
-
-        if USE_BOOK {
-            let book_move = self.find_book_move(engine, &move_list);
-            if book_move.is_some() {
-                return book_move;
+    /// Lazy SMP: helper threads run their own iterative deepening on a copy of the game state and
+    /// only cooperate through the shared transposition table, the main thread stops them once it's
+    /// done and picks the move the threads agree on the most
+    fn lazy_smp<W: Write>(
+        &mut self,
+        engine: &mut Engine,
+        writer: &mut W,
+        max_depth: u8,
+        threads: usize,
+    ) -> Option<SearchResult> {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (1..threads)
+                .map(|thread_id| {
+                    let mut helper = Engine {
+                        state: engine.state.clone(),
+                        options: engine.options.clone(),
+                        tt: Arc::clone(&engine.tt),
+                    };
+                    let mut searcher =
+                        Searcher::new_thread(self.time_limit, thread_id, Arc::clone(&self.shared));
+                    scope.spawn(move || {
+                        searcher.iterative_deepening(&mut helper, &mut std::io::sink(), max_depth)
+                    })
+                })
+                .collect();
+
+            let main_result = self.iterative_deepening(engine, writer, max_depth);
+            self.shared.stop.store(true, Ordering::Relaxed);
+
+            let mut results: Vec<SearchResult> = main_result.into_iter().collect();
+            for handle in handles {
+                results.extend(handle.join().expect("Search thread panicked"));
             }
-        }
+            let best = vote(&results);
+            // the move played comes from a helper thread, report the line it was found with
+            if let (Some(best), Some(main)) = (best, main_result)
+                && best.mv != main.mv
+            {
+                self.write_info(engine, writer, &best);
+            }
+            best
+        })
+    }
 
+    fn iterative_deepening<W: Write>(
+        &mut self,
+        engine: &mut Engine,
+        writer: &mut W,
+        max_depth: u8,
+    ) -> Option<SearchResult> {
         let mut depth = 1;
-        let mut best_move = Move::null();
-        let mut best_score = MIN;
+        let mut result = None;
 
-        // iterative deepening
-        while depth < MAX_PLY as u8 {
+        while depth <= max_depth {
             if self.should_cancel() {
                 break;
             }
 
+            if self.skip_depth(depth) {
+                depth += 1;
+                continue;
+            }
+
             self.total_moves = 0;
             self.pruned_count = 0;
             self.leaf_count = 0;
 
-            let prev_pv = self.pv_table[0];
-            let (score, mv) = self.negamax(engine, depth, depth, MIN, MAX, &prev_pv);
+            self.prev_pv = self.pv_table[0];
+            let (score, mv) = self.negamax(engine, 0, depth, MIN, MAX, Move::null());
 
             if self.should_cancel() {
                 break;
             }
 
             debug_assert!(!mv.is_null(), "Best move should be valid");
-
-            best_move = mv;
-            best_score = score;
+            let iteration = SearchResult { mv, score, depth, pv: self.root_pv() };
+            if self.thread_id == 0 {
+                self.write_info(engine, writer, &iteration);
+            }
+            result = Some(iteration);
 
             depth += 1;
         }
 
-        if_debug_search!({
-            let pv_moves = &self.pv_table[0][0..self.pv_length[0]];
-            let mut moves = String::new();
-            for mv in pv_moves.iter() {
-                if mv.is_null() {
-                    break;
-                }
-                moves.push(' ');
-                moves.push_str(&mv.to_string());
-            }
+        self.flush_nodes();
+        result
+    }
 
-            log::debug!(
-                "moves: {}(score: {}) found in {} ms, at depth: {}, {} leaves evaluated, {}/{} ({}%) pruned",
-                moves,
-                best_score,
-                self.timer.elapsed_ms(),
-                depth,
-                self.leaf_count,
-                self.pruned_count,
-                self.total_moves,
-                self.pruned_count as f32 / self.total_moves as f32 * 100.0
-            );
+    /// Helper threads skip some iterations so they spread over different depths instead of all
+    /// searching the same tree as the main thread
+    fn skip_depth(&self, depth: u8) -> bool {
+        if self.thread_id == 0 {
+            return false;
+        }
+        let i = (self.thread_id - 1) % SKIP_SIZE.len();
+        !((depth as usize + SKIP_PHASE[i]) / SKIP_SIZE[i]).is_multiple_of(2)
+    }
+}
 
-            // log::debug!(
-            //     "tt table {}/{}, {}% full, collisions: {}",
-            //     engine.tt.count(),
-            //     engine.tt.capacity(),
-            //     (engine.tt.count() as f32 / engine.tt.capacity() as f32 * 100.0).round(),
-            //     engine.tt.collision_count
-            // );
-        });
+/// Picks the result of the thread to play, every thread votes for its move with a weight growing
+/// with the depth it completed and the score it found
+fn pv_string(pv: &PVLine) -> String {
+    let moves: Vec<String> =
+        pv.iter().take_while(|mv| !mv.is_null()).map(|mv| mv.to_string()).collect();
+    moves.join(" ")
+}
+
+fn vote(results: &[SearchResult]) -> Option<SearchResult> {
+    let min_score = results.iter().map(|r| r.score).min()?;
+    let votes = |mv: Move| -> i64 {
+        results
+            .iter()
+            .filter(|r| r.mv == mv)
+            .map(|r| (r.score - min_score + 14) as i64 * r.depth as i64)
+            .sum()
+    };
+
+    let mut best = results[0];
+    for result in results.iter().copied().skip(1) {
+        let better = if best.score >= MATE_BOUND || result.score >= MATE_BOUND {
+            // a shorter mate beats any vote
+            result.score > best.score
+        } else {
+            let (result_votes, best_votes) = (votes(result.mv), votes(best.mv));
+            result_votes > best_votes || (result_votes == best_votes && result.depth > best.depth)
+        };
+        if better {
+            best = result;
+        }
+    }
+    Some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_HASH_MB: usize = 1;
+
+    #[test]
+    fn test_history_bonus_is_bounded() {
+        let mut entry = 0i16;
+        for _ in 0..100 {
+            Searcher::apply_history_bonus(&mut entry, MAX_HISTORY);
+        }
+        assert!(entry as i32 <= MAX_HISTORY);
+        assert!(entry as i32 > MAX_HISTORY - 100);
+
+        for _ in 0..100 {
+            Searcher::apply_history_bonus(&mut entry, -MAX_HISTORY);
+        }
+        assert!(entry as i32 >= -MAX_HISTORY);
+        assert!((entry as i32) < -MAX_HISTORY + 100);
+    }
+
+    #[test]
+    fn test_quiet_cutoff_updates_counter_move() {
+        let mut pos = Position::new();
+        let mut searcher = Searcher::new(f64::MAX);
+
+        let e4 = Move::new(Square::E2, Square::E4, MoveType::Normal, None);
+        searcher.stack[0] = StackEntry { piece: Piece::W_PAWN, mv: e4, ..StackEntry::NONE };
+        pos.make_move(e4);
+
+        let e5 = Move::new(Square::E7, Square::E5, MoveType::Normal, None);
+        let d5 = Move::new(Square::D7, Square::D5, MoveType::Normal, None);
+        let mut quiets_tried = MoveList::new();
+        quiets_tried.add(d5);
+        searcher.update_quiet_stats(&pos, 1, 4, e5, &quiets_tried);
+
+        assert_eq!(searcher.counter_move(1), e5);
+        assert!(searcher.is_killer(1, e5));
+        assert!(searcher.quiet_history(&pos, 1, e5) > 0);
+        assert!(searcher.quiet_history(&pos, 1, d5) < 0);
+    }
+
+    fn extension_after(fen: &str, mv: Move, line_extensions: u8) -> u8 {
+        let mut pos = Position::from_fen(fen).unwrap();
+        let mut searcher = Searcher::new(f64::MAX);
+        searcher.stack[0].extensions = line_extensions;
+
+        let piece = pos.get_piece_at(mv.src_sq());
+        let captured = pos.get_piece_at(mv.dst_sq());
+        pos.make_move(mv);
+        searcher.stack[1] = StackEntry { piece, mv, captured, extensions: line_extensions };
+        searcher.extension(&pos, 1, true, false)
+    }
+
+    #[test]
+    fn test_check_and_pawn_push_extensions() {
+        const FEN: &str = "4k3/8/1P6/8/8/8/8/R3K3 w - - 0 1";
+        let check = Move::new(Square::A1, Square::A8, MoveType::Normal, None);
+        let push = Move::new(Square::B6, Square::B7, MoveType::Normal, None);
+        let quiet = Move::new(Square::A1, Square::A2, MoveType::Normal, None);
+
+        assert_eq!(extension_after(FEN, check, 0), 1);
+        assert_eq!(extension_after(FEN, push, 0), 1);
+        assert_eq!(extension_after(FEN, quiet, 0), 0);
+
+        // a check is extended even when the checking piece can be taken
+        let attacked_check = Move::new(Square::A1, Square::A8, MoveType::Normal, None);
+        assert_eq!(extension_after("1k6/8/8/8/8/8/8/R3K3 w - - 0 1", attacked_check, 0), 1);
+
+        // no more extensions once the line has used up its budget
+        assert_eq!(extension_after(FEN, check, MAX_LINE_EXTENSIONS), 0);
+    }
+
+    #[test]
+    fn test_uci_score() {
+        assert_eq!(uci_score(35), "cp 35");
+        assert_eq!(uci_score(-120), "cp -120");
+        assert_eq!(uci_score(IMMEDIATE_MATE_SCORE - 1), "mate 1");
+        assert_eq!(uci_score(IMMEDIATE_MATE_SCORE - 3), "mate 2");
+        assert_eq!(uci_score(-(IMMEDIATE_MATE_SCORE - 2)), "mate -1");
+        assert_eq!(uci_score(-(IMMEDIATE_MATE_SCORE - 4)), "mate -2");
+    }
+
+    #[test]
+    fn test_tt_mate_score_is_node_relative() {
+        // mate found 5 plies from the root, stored at ply 2, probed again at ply 4
+        let score = IMMEDIATE_MATE_SCORE - 5;
+        let stored = score_to_tt(score, 2);
+        assert_eq!(stored, IMMEDIATE_MATE_SCORE - 3);
+        assert_eq!(score_from_tt(stored, 4), IMMEDIATE_MATE_SCORE - 7);
+        assert_eq!(score_from_tt(score_to_tt(-score, 2), 2), -score);
+        assert_eq!(score_from_tt(score_to_tt(150, 2), 7), 150);
+    }
+
+    #[test]
+    fn test_mate_distance_from_root() {
+        let fen = "r4r1k/2p1p2p/p5p1/1p1Q1p2/1P3bq1/P1P2N2/1B3P2/4R1RK b - - 0 1";
+        let mut engine = Engine::from_fen_with_hash_size(fen, TEST_HASH_MB).unwrap();
+        let mut searcher = Searcher::new(f64::MAX);
+        for depth in 1..=4 {
+            searcher.negamax(&mut engine, 0, depth, MIN, MAX, Move::null());
+        }
+        let (score, mv) = searcher.negamax(&mut engine, 0, 5, MIN, MAX, Move::null());
+        assert_eq!(mv.to_string(), "g4h3");
+        assert_eq!(uci_score(score), "mate 2");
+    }
+
+    #[test]
+    fn test_check_extensions_find_mate_below_its_depth() {
+        // mate in three is five plies deep, the checks along the line are extended
+        let fen = "Q4bk1/p2b1r2/7p/1pp5/4P1pq/2NP2P1/PPn3P1/1RB2RK1 b - - 0 1";
+        let mut engine = Engine::from_fen_with_hash_size(fen, TEST_HASH_MB).unwrap();
+        let mut searcher = Searcher::new(f64::MAX);
+        searcher.negamax(&mut engine, 0, 1, MIN, MAX, Move::null());
+        let (score, mv) = searcher.negamax(&mut engine, 0, 2, MIN, MAX, Move::null());
+        assert_eq!(mv.to_string(), "f7f1");
+        assert_eq!(uci_score(score), "mate 3");
+    }
+
+    fn quiescence_score(fen: &str, quiet_checks: bool) -> (i32, i32) {
+        let mut engine = Engine::from_fen_with_hash_size(fen, TEST_HASH_MB).unwrap();
+        let mut searcher = Searcher::new(f64::MAX);
+        let eval = searcher.evaluate(&engine.state.pos);
+        (searcher.quiescence(&mut engine, 0, MIN, MAX, quiet_checks), eval)
+    }
+
+    #[test]
+    fn test_quiescence_without_captures_stands_pat() {
+        let (score, eval) = quiescence_score("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", false);
+        assert_eq!(score, eval);
+    }
+
+    #[test]
+    fn test_quiescence_in_check() {
+        // checkmated, there's no evasion
+        let (score, _) = quiescence_score("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", false);
+        assert_eq!(score, -IMMEDIATE_MATE_SCORE);
+
+        // the knight forks king and queen, standing pat would ignore it
+        let (score, eval) = quiescence_score("7k/7p/8/8/8/Q7/2n5/4K3 w - - 0 1", false);
+        assert!(eval > 0);
+        assert!(score < 0);
+    }
+
+    #[test]
+    fn test_quiescence_quiet_checks() {
+        let fen = "6k1/5ppp/8/8/8/8/8/3R2K1 w - - 0 1";
+        let (score, _) = quiescence_score(fen, false);
+        assert!(score < MATE_BOUND);
+        let (score, _) = quiescence_score(fen, true);
+        assert_eq!(score, IMMEDIATE_MATE_SCORE - 1);
+    }
+
+    #[test]
+    fn test_multi_threaded_pv_starts_with_best_move() {
+        let fen = "r1bqk2r/pppp1ppp/2n5/2b1p3/4P1n1/3P1N2/PPPNBPPP/R1BQ1RK1 b kq - 8 6";
+        let mut engine = Engine::from_fen_with_hash_size(fen, TEST_HASH_MB).unwrap();
+        engine.options.threads = 4;
+        for _ in 0..3 {
+            let mut output = Vec::new();
+            let mv = Searcher::new(f64::MAX).search(&mut engine, &mut output, 6).unwrap();
+
+            let output = String::from_utf8(output).unwrap();
+            let last_info = output.lines().last().unwrap();
+            let pv = last_info.split(" pv ").nth(1).unwrap();
+            assert_eq!(pv.split_whitespace().next(), Some(mv.to_string().as_str()));
+        }
+    }
+
+    #[test]
+    fn test_vote() {
+        let mv1 = Move::new(Square::E2, Square::E4, MoveType::Normal, None);
+        let mv2 = Move::new(Square::D2, Square::D4, MoveType::Normal, None);
+        let result = |mv, score, depth| SearchResult { mv, score, depth, pv: [mv; MAX_PLY] };
+
+        // two threads agreeing outvote a single deeper one
+        let results = [result(mv1, 20, 8), result(mv2, 30, 9), result(mv2, 25, 8)];
+        assert_eq!(vote(&results).unwrap().mv, mv2);
+        let results = [result(mv1, 20, 10), result(mv2, 10, 6), result(mv2, 10, 6)];
+        assert_eq!(vote(&results).unwrap().mv, mv1);
+
+        // the shortest mate is always played
+        let results = [result(mv1, 500, 10), result(mv2, IMMEDIATE_MATE_SCORE - 5, 6)];
+        assert_eq!(vote(&results).unwrap().mv, mv2);
 
-        Some(best_move)
+        assert!(vote(&[]).is_none());
     }
 }
@@ -1,3 +1,8 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
 use static_assertions::const_assert;
 
 use crate::core::types::Move;
@@ -14,18 +19,97 @@ pub enum NodeType {
     UpperBound = 3,
 }
 
-#[derive(Copy, Clone)]
-#[repr(C)]
+impl NodeType {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(NodeType::Exact),
+            2 => Some(NodeType::LowerBound),
+            3 => Some(NodeType::UpperBound),
+            _ => None,
+        }
+    }
+}
+
+/// Static evaluation of an entry stored for a position in check, which isn't evaluated
+pub const EVAL_NONE: i32 = i16::MIN as i32;
+
+#[derive(Copy, Clone, Debug)]
 pub struct TTEntry {
-    pub key: ZobristHash,    // 8 bytes
-    pub score: i32,          // 4 bytes
-    pub best_move: Move,     // 2 bytes
-    pub depth: u8,           // 1 byte, means ply searched for this entry
-    pub node_type: NodeType, // 1 byte
+    pub score: i32,          // stored in 16 bits
+    pub static_eval: i32,    // stored in 16 bits, EVAL_NONE if the position wasn't evaluated
+    pub best_move: Move,     // 16 bits
+    pub depth: u8,           // 8 bits, means ply searched for this entry
+    pub node_type: NodeType, // 2 bits
+    pub is_pv: bool,         // 1 bit, whether the position was ever searched as a PV node
 }
 
-const_assert!(std::mem::size_of::<TTEntry>() == 16);
-const_assert!(std::mem::size_of::<Option<TTEntry>>() == 16);
+impl TTEntry {
+    /// Packs the entry into a single word: score in bits 0-15, static eval in 16-31, best move in
+    /// 32-47, depth in 48-55, node type in 56-57, PV flag in 58 and the generation of the search
+    /// in 59-63
+    fn pack(&self, generation: u8) -> u64 {
+        debug_assert!(self.score >= i16::MIN as i32 && self.score <= i16::MAX as i32);
+        debug_assert!(self.static_eval >= i16::MIN as i32 && self.static_eval <= i16::MAX as i32);
+        (self.score as u16 as u64)
+            | (self.static_eval as u16 as u64) << 16
+            | (self.best_move.as_u16() as u64) << 32
+            | (self.depth as u64) << 48
+            | (self.node_type as u64) << 56
+            | (self.is_pv as u64) << 58
+            | (generation as u64) << 59
+    }
+
+    fn unpack(data: u64) -> Option<Self> {
+        let node_type = NodeType::from_u8((data >> 56) as u8 & 0b11)?;
+        Some(TTEntry {
+            score: data as u16 as i16 as i32,
+            static_eval: (data >> 16) as u16 as i16 as i32,
+            best_move: Move::from_u16((data >> 32) as u16),
+            depth: (data >> 48) as u8,
+            node_type,
+            is_pv: (data >> 58) & 1 != 0,
+        })
+    }
+}
+
+const GENERATION_BITS: u32 = 5;
+const GENERATION_MASK: u8 = (1 << GENERATION_BITS) - 1;
+
+fn generation_of(data: u64) -> u8 {
+    (data >> 59) as u8
+}
+
+/// A slot of the table, stores `key ^ data` next to `data` so that a torn read, where the two
+/// words were written by different threads, fails the key check instead of returning a corrupted
+/// entry. Empty slots have a zero data word, which decodes to no node type.
+struct AtomicEntry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl AtomicEntry {
+    /// Returns the key and the data word, the key is only right if the entry wasn't torn
+    fn load(&self) -> (ZobristHash, u64) {
+        let data = self.data.load(Ordering::Relaxed);
+        (ZobristHash(self.key.load(Ordering::Relaxed) ^ data), data)
+    }
+
+    fn write(&self, key: ZobristHash, data: u64) {
+        self.data.store(data, Ordering::Relaxed);
+        self.key.store(key.0 ^ data, Ordering::Relaxed);
+    }
+}
+
+const BUCKET_SIZE: usize = 4;
+
+/// Entries of a bucket share a cache line, a position can be stored in any of them
+#[repr(align(64))]
+struct Bucket {
+    entries: [AtomicEntry; BUCKET_SIZE],
+}
+
+const_assert!(std::mem::size_of::<AtomicEntry>() == 16);
+const_assert!(std::mem::size_of::<Bucket>() == 64);
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TTStoreResult {
@@ -35,101 +119,441 @@ pub enum TTStoreResult {
     OverridenNoCollision, // entry with the same hash, same key already exists
 }
 
-pub struct TranspositionTable<const N: usize> {
-    table: Box<[Option<TTEntry>]>,
-    count: u64,
-
-    pub collision_count: u64,
+/// Lock-free transposition table, shared by all the search threads through `&self`
+pub struct TranspositionTable {
+    buckets: Box<[Bucket]>,
+    generation: AtomicU8, // bumped for every search, tells stale entries apart
 }
 
-impl<const N: usize> TranspositionTable<N> {
-    pub fn new() -> Self {
-        let data = vec![None; N];
-        let table = data.into_boxed_slice();
-        Self { table, count: 0, collision_count: 0 }
+impl TranspositionTable {
+    pub const DEFAULT_SIZE_MB: usize = 64;
+    pub const MAX_SIZE_MB: usize = 4096;
+
+    /// Creates a table using as many buckets as fit in `size_mb` megabytes
+    pub fn new(size_mb: usize) -> Self {
+        let len = (size_mb.max(1) * 1024 * 1024 / std::mem::size_of::<Bucket>()).max(1);
+        Self::with_buckets(len)
     }
 
+    fn with_buckets(len: usize) -> Self {
+        let buckets = (0..len)
+            .map(|_| Bucket {
+                entries: std::array::from_fn(|_| AtomicEntry {
+                    key: AtomicU64::new(0),
+                    data: AtomicU64::new(0),
+                }),
+            })
+            .collect();
+        Self { buckets, generation: AtomicU8::new(0) }
+    }
+
+    /// Maps the key to a bucket with a multiply-shift, so the size doesn't need to be a power of two
     #[inline(always)]
-    fn index(key: ZobristHash) -> usize {
-        (key.0 as usize) & (N - 1)
+    fn bucket(&self, key: ZobristHash) -> &Bucket {
+        &self.buckets[((key.0 as u128 * self.buckets.len() as u128) >> 64) as usize]
     }
 
-    #[allow(dead_code)]
-    pub const fn capacity(&self) -> usize {
-        N
+    /// Size of the table in megabytes, as passed to `new`
+    pub fn size_mb(&self) -> usize {
+        (self.buckets.len() * std::mem::size_of::<Bucket>() / (1024 * 1024)).max(1)
     }
 
     #[allow(dead_code)]
-    pub fn count(&self) -> u64 {
-        self.count
-    }
-
-    pub fn store(
-        &mut self,
-        key: ZobristHash,
-        depth: u8,
-        score: i32,
-        node_type: NodeType,
-        best_move: Move,
-    ) -> TTStoreResult {
-        debug_assert!(!best_move.is_null());
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    /// Empties the table, entries from a previous game are only noise for the next one
+    pub fn clear(&self) {
+        for entry in self.buckets.iter().flat_map(|bucket| bucket.entries.iter()) {
+            entry.key.store(0, Ordering::Relaxed);
+            entry.data.store(0, Ordering::Relaxed);
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    /// Starts a new generation, entries stored by previous searches become replaceable first
+    pub fn new_search(&self) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation.store((generation + 1) & GENERATION_MASK, Ordering::Relaxed);
+    }
+
+    fn current_generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Number of searches since the entry was stored
+    fn age(&self, data: u64) -> u8 {
+        self.current_generation().wrapping_sub(generation_of(data)) & GENERATION_MASK
+    }
+
+    /// How much the table is used by the current search, in per mille, estimated from the first
+    /// thousand entries
+    pub fn hashfull(&self) -> usize {
+        let sample = self.buckets.iter().take(1000 / BUCKET_SIZE).flat_map(|b| b.entries.iter());
+        let (mut used, mut total) = (0, 0);
+        for slot in sample {
+            let (_, data) = slot.load();
+            if TTEntry::unpack(data).is_some() && self.age(data) == 0 {
+                used += 1;
+            }
+            total += 1;
+        }
+        used * 1000 / total
+    }
+
+    pub fn store(&self, key: ZobristHash, entry: TTEntry) -> TTStoreResult {
+        debug_assert!(!entry.best_move.is_null());
         // Don't store entries with if debug option is enabled
         if DEBUG_OPTION_TURN_OFF_TT {
             return TTStoreResult::NotUpdated;
         }
 
-        let idx = Self::index(key);
-        let existing = &self.table[idx];
-
-        let result = if let Some(old_entry) = existing {
-            if old_entry.key != key {
-                self.collision_count += 1;
-                TTStoreResult::OverridenCollision
-            } else if depth >= old_entry.depth {
-                TTStoreResult::OverridenNoCollision
-            } else {
-                TTStoreResult::NotUpdated
+        let bucket = self.bucket(key);
+        let mut replace = &bucket.entries[0];
+        let mut replace_worth = i32::MAX;
+        let mut result = TTStoreResult::OverridenCollision;
+        for slot in bucket.entries.iter() {
+            let (old_key, old_data) = slot.load();
+            let Some(old_entry) = TTEntry::unpack(old_data) else {
+                if replace_worth > i32::MIN {
+                    // an empty slot is the best choice unless the position is already stored
+                    replace = slot;
+                    replace_worth = i32::MIN;
+                    result = TTStoreResult::UpdatedEmpty;
+                }
+                continue;
+            };
+
+            if old_key == key {
+                // a deeper result of the same search is kept
+                if entry.depth < old_entry.depth && self.age(old_data) == 0 {
+                    return TTStoreResult::NotUpdated;
+                }
+                replace = slot;
+                result = TTStoreResult::OverridenNoCollision;
+                break;
             }
-        } else {
-            self.count += 1; // only increment count if we are inserting a new entry
-            TTStoreResult::UpdatedEmpty
-        };
 
-        if result != TTStoreResult::NotUpdated {
-            self.table[idx] = Some(TTEntry { key, depth, score, node_type, best_move });
+            // prefer replacing entries from older searches, then shallower ones
+            let worth = old_entry.depth as i32 - 8 * self.age(old_data) as i32;
+            if worth < replace_worth {
+                replace = slot;
+                replace_worth = worth;
+            }
         }
 
+        let data = entry.pack(self.current_generation());
+        replace.write(key, data);
         result
     }
 
-    pub fn probe(&self, key: ZobristHash) -> Option<&TTEntry> {
+    /// Writes the table to a file, so that a later session can resume the analysis with
+    /// `TranspositionTable::load`
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let header = FileHeader {
+            version: FileHeader::VERSION,
+            entry_layout: FileHeader::ENTRY_LAYOUT,
+            key_scheme: FileHeader::KEY_SCHEME,
+            bucket_size: BUCKET_SIZE as u32,
+            generation: self.current_generation() as u32,
+            bucket_count: self.buckets.len() as u64,
+        };
+        header.write(&mut writer)?;
+
+        for slot in self.buckets.iter().flat_map(|bucket| bucket.entries.iter()) {
+            writer.write_all(&slot.key.load(Ordering::Relaxed).to_le_bytes())?;
+            writer.write_all(&slot.data.load(Ordering::Relaxed).to_le_bytes())?;
+        }
+        writer.flush()
+    }
+
+    /// Reads a table written by `TranspositionTable::save`, the table gets the size of the file
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let header = FileHeader::read(&mut reader)?;
+        if header.version != FileHeader::VERSION
+            || header.entry_layout != FileHeader::ENTRY_LAYOUT
+            || header.key_scheme != FileHeader::KEY_SCHEME
+            || header.bucket_size != BUCKET_SIZE as u32
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "transposition table file was written by an incompatible version",
+            ));
+        }
+        let max_buckets = (Self::MAX_SIZE_MB * 1024 * 1024 / std::mem::size_of::<Bucket>()) as u64;
+        if header.bucket_count == 0 || header.bucket_count > max_buckets {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid table size"));
+        }
+
+        let table = Self::with_buckets(header.bucket_count as usize);
+        table.generation.store(header.generation as u8 & GENERATION_MASK, Ordering::Relaxed);
+        for slot in table.buckets.iter().flat_map(|bucket| bucket.entries.iter()) {
+            slot.key.store(read_u64(&mut reader)?, Ordering::Relaxed);
+            slot.data.store(read_u64(&mut reader)?, Ordering::Relaxed);
+        }
+        Ok(table)
+    }
+
+    pub fn probe(&self, key: ZobristHash) -> Option<TTEntry> {
         debug_assert!(key.0 != 0, "ZobristHash cannot be zero");
-        let idx = Self::index(key);
-        let entry = &self.table[idx];
-        match entry {
-            Some(e) => {
-                if e.key == key {
-                    Some(e)
-                } else {
-                    None
-                }
-            }
-            None => None,
+        self.bucket(key).entries.iter().find_map(|slot| {
+            let (slot_key, data) = slot.load();
+            if slot_key == key { TTEntry::unpack(data) } else { None }
+        })
+    }
+}
+
+/// Header of a saved table, all the fields are little endian
+struct FileHeader {
+    version: u32,
+    entry_layout: u32, // bump whenever `TTEntry::pack` or `AtomicEntry` change
+    key_scheme: u32,   // bump whenever the zobrist keys change
+    bucket_size: u32,
+    generation: u32,
+    bucket_count: u64,
+}
+
+impl FileHeader {
+    const MAGIC: &[u8; 8] = b"BBXTTABL";
+    const VERSION: u32 = 1;
+    const ENTRY_LAYOUT: u32 = 1;
+    const KEY_SCHEME: u32 = 1; // polyglot keys
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(Self::MAGIC)?;
+        for field in
+            [self.version, self.entry_layout, self.key_scheme, self.bucket_size, self.generation]
+        {
+            writer.write_all(&field.to_le_bytes())?;
         }
+        writer.write_all(&self.bucket_count.to_le_bytes())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != Self::MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a transposition table file",
+            ));
+        }
+        Ok(Self {
+            version: read_u32(reader)?,
+            entry_layout: read_u32(reader)?,
+            key_scheme: read_u32(reader)?,
+            bucket_size: read_u32(reader)?,
+            generation: read_u32(reader)?,
+            bucket_count: read_u64(reader)?,
+        })
     }
 }
 
-const DEFAULT_TT_SIZE_IN_BYTE: usize = 64 * 1024 * 1024; // 32 MB
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
 
-pub type TTable = TranspositionTable<{ DEFAULT_TT_SIZE_IN_BYTE / std::mem::size_of::<TTEntry>() }>;
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub type TTable = TranspositionTable;
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::types::{MoveType, PieceType, Square};
+
+    fn new_entry(depth: u8, score: i32, node_type: NodeType, best_move: Move) -> TTEntry {
+        TTEntry { score, static_eval: EVAL_NONE, best_move, depth, node_type, is_pv: false }
+    }
 
     #[test]
     fn test_tt_entry_size() {
-        assert_eq!(std::mem::size_of::<TTEntry>(), 16);
-        assert_eq!(std::mem::size_of::<Option<TTEntry>>(), 16);
+        assert_eq!(std::mem::size_of::<AtomicEntry>(), 16);
+        assert_eq!(std::mem::size_of::<Bucket>(), 64);
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let tt = TranspositionTable::new(1);
+        let key = ZobristHash(0x1234_5678_9abc_def0);
+        let mv = Move::new(Square::E7, Square::E8, MoveType::Promotion, Some(PieceType::QUEEN));
+        assert!(tt.probe(key).is_none());
+
+        assert_eq!(
+            tt.store(key, new_entry(5, -1234, NodeType::LowerBound, mv)),
+            TTStoreResult::UpdatedEmpty
+        );
+        let entry = tt.probe(key).unwrap();
+        assert_eq!(entry.score, -1234);
+        assert_eq!(entry.best_move, mv);
+        assert_eq!(entry.depth, 5);
+        assert!(matches!(entry.node_type, NodeType::LowerBound));
+
+        // a shallower search of the same position doesn't replace the entry
+        assert_eq!(tt.store(key, new_entry(4, 0, NodeType::Exact, mv)), TTStoreResult::NotUpdated);
+        assert_eq!(tt.probe(key).unwrap().depth, 5);
+
+        // other positions mapping to the same bucket use its free entries
+        let other = ZobristHash(key.0 ^ 1);
+        assert_eq!(
+            tt.store(other, new_entry(1, 7, NodeType::Exact, mv)),
+            TTStoreResult::UpdatedEmpty
+        );
+        assert_eq!(tt.probe(key).unwrap().score, -1234);
+        assert_eq!(tt.probe(other).unwrap().score, 7);
+    }
+
+    #[test]
+    fn test_entry_packing() {
+        let tt = TranspositionTable::new(1);
+        let key = ZobristHash(42);
+        let mv = Move::new(Square::H7, Square::H8, MoveType::Promotion, Some(PieceType::KNIGHT));
+        let stored = TTEntry {
+            score: -31990,
+            static_eval: -250,
+            best_move: mv,
+            depth: 255,
+            node_type: NodeType::UpperBound,
+            is_pv: true,
+        };
+        for _ in 0..40 {
+            tt.new_search(); // the generation wraps around without touching the other fields
+            tt.store(key, stored);
+            let entry = tt.probe(key).unwrap();
+            assert_eq!(entry.score, stored.score);
+            assert_eq!(entry.static_eval, stored.static_eval);
+            assert_eq!(entry.best_move, mv);
+            assert_eq!(entry.depth, 255);
+            assert!(matches!(entry.node_type, NodeType::UpperBound));
+            assert!(entry.is_pv);
+        }
+    }
+
+    #[test]
+    fn test_replacement_policy() {
+        let tt = TranspositionTable::new(1);
+        let mv = Move::new(Square::A1, Square::A2, MoveType::Normal, None);
+        // keys differing in the low bits only land in the same bucket
+        let key = |i: u64| ZobristHash(0xabcd_0000_0000_0000 | i);
+
+        tt.store(key(1), new_entry(10, 0, NodeType::Exact, mv));
+        tt.store(key(2), new_entry(2, 0, NodeType::Exact, mv));
+        tt.store(key(3), new_entry(12, 0, NodeType::Exact, mv));
+        tt.store(key(4), new_entry(11, 0, NodeType::Exact, mv));
+
+        // the bucket is full, the shallowest entry goes
+        assert_eq!(
+            tt.store(key(5), new_entry(1, 0, NodeType::Exact, mv)),
+            TTStoreResult::OverridenCollision
+        );
+        assert!(tt.probe(key(2)).is_none());
+
+        // entries of previous searches go before entries of the current one of similar depth
+        tt.new_search();
+        tt.store(key(5), new_entry(3, 0, NodeType::Exact, mv));
+        tt.new_search();
+        tt.store(key(6), new_entry(5, 0, NodeType::Exact, mv));
+        assert!(tt.probe(key(5)).is_some());
+        assert!(tt.probe(key(1)).is_none());
+        assert!(tt.probe(key(3)).is_some());
+        assert!(tt.probe(key(4)).is_some());
+
+        // a stale entry of the same position is replaced even by a shallower search
+        tt.new_search();
+        assert_eq!(
+            tt.store(key(3), new_entry(3, 5, NodeType::Exact, mv)),
+            TTStoreResult::OverridenNoCollision
+        );
+        assert_eq!(tt.probe(key(3)).unwrap().score, 5);
+    }
+
+    #[test]
+    fn test_hashfull() {
+        let tt = TranspositionTable::new(1);
+        let mv = Move::new(Square::A1, Square::A2, MoveType::Normal, None);
+        assert_eq!(tt.hashfull(), 0);
+        for i in 0..1_000_000u64 {
+            let key = ZobristHash(i.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1);
+            tt.store(key, new_entry(1, 0, NodeType::Exact, mv));
+        }
+        assert!(tt.hashfull() > 900);
+
+        // entries of previous searches don't count
+        tt.new_search();
+        assert_eq!(tt.hashfull(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_access() {
+        let tt = TranspositionTable::new(1);
+        std::thread::scope(|scope| {
+            for thread in 0..4u64 {
+                let tt = &tt;
+                scope.spawn(move || {
+                    for i in 1..10_000u64 {
+                        // every thread writes entries whose score is derived from the key, so a
+                        // mix of two writes would be detected
+                        let key = ZobristHash(i.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ thread);
+                        let mv = Move::new(Square::A1, Square::A2, MoveType::Normal, None);
+                        let score = key.0 as i16 as i32;
+                        tt.store(key, new_entry((i % 64) as u8, score, NodeType::Exact, mv));
+                        if let Some(entry) = tt.probe(key) {
+                            assert_eq!(entry.score, score);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_size_and_clear() {
+        let tt = TranspositionTable::new(3);
+        assert_eq!(tt.capacity(), 3 * 1024 * 1024 / 16);
+
+        let key = ZobristHash(u64::MAX);
+        let mv = Move::new(Square::A1, Square::A2, MoveType::Normal, None);
+        tt.store(key, new_entry(1, 0, NodeType::Exact, mv));
+        assert!(tt.probe(key).is_some());
+        tt.clear();
+        assert!(tt.probe(key).is_none());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("bbx_tt_{}.bin", std::process::id()));
+        let tt = TranspositionTable::new(1);
+        let mv = Move::new(Square::G1, Square::F3, MoveType::Normal, None);
+        let key = ZobristHash(0x0123_4567_89ab_cdef);
+        tt.new_search();
+        tt.store(key, new_entry(9, 35, NodeType::Exact, mv));
+        tt.save(&path).unwrap();
+
+        let loaded = TranspositionTable::load(&path).unwrap();
+        assert_eq!(loaded.capacity(), tt.capacity());
+        assert_eq!(loaded.current_generation(), tt.current_generation());
+        let entry = loaded.probe(key).unwrap();
+        assert_eq!(entry.score, 35);
+        assert_eq!(entry.depth, 9);
+        assert_eq!(entry.best_move, mv);
+
+        // files of another layout are rejected
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[12] += 1; // entry layout
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(TranspositionTable::load(&path).is_err());
+        std::fs::write(&path, b"garbage").unwrap();
+        assert!(TranspositionTable::load(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -1,8 +1,11 @@
 use bitboard_x::core::{position::Position, types::*};
-use bitboard_x::engine::Engine;
+use bitboard_x::engine::{
+    BENCH_DEPTH, DEFAULT_HASH_MB, Engine, InternalIterative, MAX_HASH_MB, MAX_THREADS,
+};
 use bitboard_x::utils::*;
 use std::io::Write;
 use std::io::{self, BufRead};
+use std::path::Path;
 
 fn main() {
     unsafe {
@@ -41,8 +44,11 @@ fn main() {
                             eprintln!("Error: {}", err);
                         }
                     },
+                    "setoption" => uci_cmd_setoption(&mut engine, args),
                     "go" => uci_cmd_go(&mut engine, &mut stdout, args),
+                    "bench" => uci_cmd_bench(&engine, &mut stdout, args),
                     "d" => uci_cmd_d(&mut engine, &mut stdout),
+                    "eval" => uci_cmd_eval(&engine, &mut stdout),
                     "q" | "quit" => {
                         break;
                     }
@@ -60,23 +66,93 @@ fn main() {
 }
 
 pub fn uci_cmd_isready<W: Write>(writer: &mut W) {
+    // the GUI waits for readyok, a good time to build what the search would build lazily
+    Engine::warm_up();
     writeln!(writer, "readyok").unwrap();
 }
 
 pub fn uci_cmd_ucinewgame<W: Write>(engine: &mut Engine, _: &mut W) {
     engine.state.set_position(Position::new());
+    engine.clear();
 }
 
 pub fn uci_cmd_uci<W: Write>(writer: &mut W) {
     writeln!(writer, "id name {}", Engine::name()).unwrap();
     writeln!(writer, "id author haguo").unwrap();
+    writeln!(
+        writer,
+        "option name InternalIterative type combo default Reduction var Off var Reduction var Deepening"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "option name Hash type spin default {} min 1 max {}",
+        DEFAULT_HASH_MB, MAX_HASH_MB
+    )
+    .unwrap();
+    writeln!(writer, "option name Threads type spin default 1 min 1 max {}", MAX_THREADS).unwrap();
+    writeln!(writer, "option name HashFile type string default hash.bin").unwrap();
+    writeln!(writer, "option name SaveHashToFile type button").unwrap();
+    writeln!(writer, "option name LoadHashFromFile type button").unwrap();
     writeln!(writer, "uciok").unwrap();
 }
 
+pub fn uci_cmd_setoption(engine: &mut Engine, args: &str) {
+    let parts: Vec<&str> = args.split_whitespace().collect();
+
+    match parts.as_slice() {
+        ["name", "InternalIterative", "value", value] => match InternalIterative::parse(value) {
+            Some(mode) => engine.options.internal_iterative = mode,
+            None => eprintln!("Error: Invalid InternalIterative value '{}'", value),
+        },
+        ["name", "Hash", "value", value] => match value.parse() {
+            Ok(hash_mb) if (1..=MAX_HASH_MB).contains(&hash_mb) => engine.set_hash_size(hash_mb),
+            _ => eprintln!("Error: Invalid Hash value '{}'", value),
+        },
+        ["name", "Threads", "value", value] => match value.parse() {
+            Ok(threads) if (1..=MAX_THREADS).contains(&threads) => engine.options.threads = threads,
+            _ => eprintln!("Error: Invalid Threads value '{}'", value),
+        },
+        ["name", "HashFile", "value", path @ ..] if !path.is_empty() => {
+            engine.options.hash_file = path.join(" ");
+        }
+        ["name", "SaveHashToFile"] => {
+            if let Err(err) = engine.save_hash(Path::new(&engine.options.hash_file)) {
+                eprintln!("Error: Cannot save hash to '{}': {}", engine.options.hash_file, err);
+            }
+        }
+        ["name", "LoadHashFromFile"] => {
+            let path = engine.options.hash_file.clone();
+            if let Err(err) = engine.load_hash(Path::new(&path)) {
+                eprintln!("Error: Cannot load hash from '{}': {}", path, err);
+            }
+        }
+        _ => eprintln!("Error: Unknown option '{}'", args),
+    }
+}
+
+pub fn uci_cmd_bench<W: Write>(engine: &Engine, writer: &mut W, args: &str) {
+    let depth = match args.trim() {
+        "" => BENCH_DEPTH,
+        depth => match depth.parse() {
+            Ok(d) if d > 0 => d,
+            _ => {
+                eprintln!("Error: Invalid depth '{}'.", depth);
+                return;
+            }
+        },
+    };
+    engine.bench(writer, depth);
+}
+
 pub fn uci_cmd_d<W: Write>(engine: &Engine, writer: &mut W) {
     print_board(writer, &engine.state.pos);
 }
 
+pub fn uci_cmd_eval<W: Write>(engine: &Engine, writer: &mut W) {
+    write!(writer, "{}", engine.eval_trace()).unwrap();
+}
+
 pub fn uci_cmd_go<W: Write>(engine: &mut Engine, writer: &mut W, args: &str) {
     let parts: Vec<&str> = args.split_whitespace().collect();
 
@@ -100,7 +176,7 @@ pub fn uci_cmd_go<W: Write>(engine: &mut Engine, writer: &mut W, args: &str) {
             let time = time as f64 / movestogo as f64;
             let time = time * 0.9;
 
-            let mv = engine.best_move(time).unwrap();
+            let mv = engine.search(writer, time).unwrap();
             writeln!(writer, "bestmove {}", mv.to_string()).unwrap();
         }
         _ => panic!(
@@ -9,7 +9,8 @@ use bitboard_x::named_test;
 const DEFAULT_DEPTH: u8 = if cfg!(not(debug_assertions)) { 8 } else { 5 };
 
 fn perft_test_wrapper(fen: &str, depth: u8, expectations: &Vec<u64>) {
-    let engine = bitboard_x::engine::Engine::from_fen(fen).unwrap();
+    // perft doesn't use the transposition table
+    let engine = bitboard_x::engine::Engine::from_fen_with_hash_size(fen, 1).unwrap();
 
     let mut out = std::io::sink();
 